@@ -0,0 +1,39 @@
+use advent_of_code_2024::day14::{simulate_lock_free, simulate_locked, Robot, HEIGHT, WIDTH};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const ROBOTS: usize = 500;
+const SECONDS: u32 = 10_000;
+const WIDTH_REQUIRED: usize = 20;
+
+//scattered robots that never draw the tree, so both versions run every second
+fn robots() -> Vec<Robot> {
+    let mut seed = 2024u64;
+    let mut next = |modulo: i16| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((seed >> 33) % modulo as u64) as i16
+    };
+    (0..ROBOTS)
+        .map(|_| {
+            let position = (next(WIDTH), next(HEIGHT));
+            let speed = (next(WIDTH) - WIDTH / 2, next(HEIGHT) - HEIGHT / 2);
+            Robot::new(position, speed)
+        })
+        .collect()
+}
+
+fn simulation(c: &mut Criterion) {
+    let robots = robots();
+    let mut group = c.benchmark_group("day14 simulation");
+    group.sample_size(10);
+
+    group.bench_function("locked", |b| {
+        b.iter(|| simulate_locked(&robots, WIDTH, HEIGHT, SECONDS, WIDTH_REQUIRED))
+    });
+    group.bench_function("lock free", |b| {
+        b.iter(|| simulate_lock_free(&robots, WIDTH, HEIGHT, SECONDS, WIDTH_REQUIRED))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, simulation);
+criterion_main!(benches);
@@ -1,17 +1,18 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{lines, AocError};
 
 #[aoc_generator(day1)]
-pub fn generate_input(input: &str) -> (Vec<i32>, Vec<i32>) {
-        input
-        .lines()
+pub fn generate_input(input: &str) -> Result<(Vec<i32>, Vec<i32>), AocError> {
+    lines(1, input)
         .map(|line| {
-            let mut parts = line.split_whitespace();
-            (
-                parts.next().unwrap().parse::<i32>().unwrap(),
-                parts.next().unwrap().parse::<i32>().unwrap(),
-            )
+            let mut parts = line.text.split_whitespace();
+            Ok((
+                line.parse_next::<i32>(&mut parts, "two location ids")?,
+                line.parse_next::<i32>(&mut parts, "two location ids")?,
+            ))
         })
-        .unzip()
+        .collect::<Result<Vec<_>, _>>()
+        .map(|pairs| pairs.into_iter().unzip())
 }
 
 //part 1
@@ -1,22 +1,25 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{AocError, Line};
 
 #[aoc_generator(day2)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Vec<Vec<i32>>, AocError> {
+    input.lines()
+         .enumerate()
+         .map(|(index, line)| get_vec(Line::new(2, index, line)))
+         .collect()
 }
 
 //part 1
 #[aoc(day2, part1)]
-pub fn solve_part1(input: &[String]) -> usize {
-    input.iter().map(|line| get_vec(line)).filter(|arr| is_valid(arr)).count()
+pub fn solve_part1(input: &[Vec<i32>]) -> usize {
+    input.iter().filter(|arr| is_valid(arr)).count()
 }
 
 //part 2
 #[aoc(day2, part2)]
-pub fn solve_part2(input: &[String]) -> usize {
+pub fn solve_part2(input: &[Vec<i32>]) -> usize {
     let mut valids = 0;
-    let invalid: Vec<Vec<i32>> = input.iter()
-                           .map(|line| get_vec(line))
+    let invalid: Vec<&Vec<i32>> = input.iter()
                            .filter(|arr|{
                                let is_valid_seq = is_valid(arr);
                                valids += if is_valid_seq { 1 } else { 0 };
@@ -56,8 +59,8 @@ fn is_valid(arr: &[i32]) -> bool {
 }
 
 
-fn get_vec(line: &str) -> Vec<i32> {
-    line.split_whitespace()
-       .map(|num| num.parse::<i32>().unwrap())
+fn get_vec(line: Line) -> Result<Vec<i32>, AocError> {
+    line.text.split_whitespace()
+       .map(|num| line.parse::<i32>(num))
        .collect()
 }
@@ -1,23 +1,34 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(i32, i32),
+    Do,
+    Dont,
+}
+
 #[aoc_generator(day3)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Vec<Instruction> {
+    //regex patern, each line is scanned on its own
+    let re = Regex::new(r"mul\((\d+),(\d+)\)|do\(\)|don't\(\)").unwrap();
+
+    input.lines()
+      .flat_map(|line| re.captures_iter(line))
+      .filter_map(|cap| match &cap[0] {
+          "do()" => Some(Instruction::Do),
+          "don't()" => Some(Instruction::Dont),
+          _ => Some(Instruction::Mul(cap[1].parse().ok()?, cap[2].parse().ok()?)),
+      })
+      .collect()
 }
 
 #[aoc(day3, part1)]
-pub fn solve_part1(input: &[String]) -> i32 {
-    //regex patern 
-    let re = Regex::new(r"mul\((\d+),(\d+)\)").unwrap();
-
-    input.into_iter()
-         .flat_map(|line| {
-            re.captures_iter(line).filter_map(|cap| {
-                let num1 = cap[1].parse::<i32>().ok()?;
-                let num2 = cap[2].parse::<i32>().ok()?;
-                Some(num1*num2)
-            })
+pub fn solve_part1(input: &[Instruction]) -> i32 {
+    input.iter()
+         .map(|instruction| match instruction {
+             Instruction::Mul(num1, num2) => num1 * num2,
+             _ => 0,
          })
          .sum()
 }
@@ -26,26 +37,45 @@ pub fn solve_part1(input: &[String]) -> i32 {
 
 //part 2
 #[aoc(day3, part2)]
-pub fn solve_part2(input: &[String]) -> i32 {
-    let re = Regex::new(r"mul\((\d+),(\d+)\)|do\(\)|don't\(\)").unwrap();
+pub fn solve_part2(input: &[Instruction]) -> i32 {
     let mut skip = false;
-    
-    re.captures_iter(&input.join(""))
-      .filter_map(|cap| {
-          let token = &cap[0];
-          if token == "don't()" {
-              skip = true; 
-              None
-          } else if token == "do()" {
-              skip = false;
-              None
-          } else if !skip {
-              let num1: i32 = cap[1].parse().unwrap(); 
-              let num2: i32 = cap[2].parse().unwrap(); 
-              Some(num1 * num2) 
-          } else {
-              None 
+
+    input.iter()
+      .filter_map(|instruction| {
+          match instruction {
+              Instruction::Dont => {
+                  skip = true;
+                  None
+              }
+              Instruction::Do => {
+                  skip = false;
+                  None
+              }
+              Instruction::Mul(num1, num2) if !skip => Some(num1 * num2),
+              Instruction::Mul(_, _) => None,
           }
       })
       .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day3() {
+        let input = generate_input(
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
+        );
+        assert_eq!(solve_part1(&input), 161);
+        assert_eq!(solve_part2(&input), 48);
+    }
+
+    #[test]
+    fn test_day3_lines() {
+        //an instruction broken over two lines doesn't count
+        let input = generate_input("mul(2,\n3)mul(1000,2)do()\nmul(4,5)");
+        assert_eq!(input, vec![Instruction::Mul(1000, 2), Instruction::Do, Instruction::Mul(4, 5)]);
+        assert_eq!(solve_part1(&input), 2020);
+    }
+}
@@ -1,47 +1,49 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{AocError, Line};
 
-#[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    input
-        .split("\n\n")
-        .map(|section| section.to_string())
-        .collect()
-}
+pub type Rule = (i32, i32);
+pub type Update = Vec<i32>;
 
-#[aoc(day5, part1)]
-pub fn solve_part1(input: &[String]) -> i32 {
-
-    let mut sections = input.iter();
+#[aoc_generator(day5)]
+pub fn input_generator(input: &str) -> Result<(Vec<Rule>, Vec<Update>), AocError> {
+    let mut sections = input.split("\n\n");
+    let rules = sections.next().ok_or(AocError::MissingInput { day: 5, expected: "ordering rules" })?;
+    let updates = sections.next().ok_or(AocError::MissingInput { day: 5, expected: "updates" })?;
 
     //tuples
-    let tuples: Vec<(i32, i32)> = sections
-        .next()
-        .unwrap()
+    let tuples = rules
         .lines()
-        .map(|line| {
-            let mut parts = line.split('|');
-            (
-                parts.next().unwrap().parse::<i32>().unwrap(),
-                parts.next().unwrap().parse::<i32>().unwrap(),
-            )
+        .enumerate()
+        .map(|(index, text)| {
+            let line = Line::new(5, index, text);
+            let mut parts = text.split('|');
+            Ok((
+                line.parse_next::<i32>(&mut parts, "a rule like 47|53")?,
+                line.parse_next::<i32>(&mut parts, "a rule like 47|53")?,
+            ))
         })
-        .collect();
+        .collect::<Result<Vec<_>, AocError>>()?;
 
-    //lists
-    let lists: Vec<Vec<i32>> = sections
-        .next()
-        .unwrap()
+    //lists, numbered after the rules and the blank line between them
+    let offset = rules.lines().count() + 1;
+    let lists = updates
         .lines()
-        .map(|line| {
-            line
-                .split(',')
-                .map(|x| x.trim().parse::<i32>().unwrap())
-                .collect()
+        .enumerate()
+        .map(|(index, text)| {
+            let line = Line::new(5, offset + index, text);
+            text.split(',').map(|x| line.parse::<i32>(x)).collect()
         })
-        .collect();
+        .collect::<Result<Vec<_>, AocError>>()?;
+
+    Ok((tuples, lists))
+}
+
+#[aoc(day5, part1)]
+pub fn solve_part1(input: &(Vec<Rule>, Vec<Update>)) -> i32 {
+    let (tuples, lists) = input;
 
     lists
-        .into_iter()
+        .iter()
         .filter(|list| {
             tuples.iter().all(|(a, b)| {
                 let pos_a = list.iter().position(|&x| x == *a);
@@ -53,42 +55,16 @@ pub fn solve_part1(input: &[String]) -> i32 {
                 }
             })
         })
-        .filter_map(|list| list.iter().nth(list.len() / 2).cloned())
+        .filter_map(|list| list.get(list.len() / 2).cloned())
         .sum::<i32>()
 }
 
 #[aoc(day5, part2)]
-pub fn solve_part2(input: &[String]) -> i32 {
-    let mut sections = input.iter();
+pub fn solve_part2(input: &(Vec<Rule>, Vec<Update>)) -> i32 {
+    let (tuples, lists) = input;
 
-    //tuples
-    let tuples: Vec<(i32, i32)> = sections
-        .next()
-        .unwrap()
-        .lines()
-        .map(|line| {
-            let mut parts = line.split('|');
-            (
-                parts.next().unwrap().parse::<i32>().unwrap(),
-                parts.next().unwrap().parse::<i32>().unwrap(),
-            )
-        })
-        .collect();
-
-    //lists
-    let lists: Vec<Vec<i32>> = sections
-        .next()
-        .unwrap()
-        .lines()
-        .map(|line| {
-            line
-                .split(',')
-                .map(|x| x.trim().parse::<i32>().unwrap())
-                .collect()
-        })
-        .collect();
     lists
-        .into_iter()
+        .iter()
         .filter(|list| {
             !tuples.iter().all(|(a, b)| {
                 let pos_a = list.iter().position(|&x| x == *a);
@@ -113,7 +89,6 @@ pub fn solve_part2(input: &[String]) -> i32 {
             });
             sorted
         })
-        .filter_map(|list| list.iter().nth(list.len() / 2).cloned())
+        .filter_map(|list| list.get(list.len() / 2).cloned())
         .sum::<i32>()
-
-}
\ No newline at end of file
+}
@@ -1,37 +1,50 @@
-use std::collections::HashMap;
 use std::collections::HashSet;
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::AocError;
+use crate::utils::Grid;
+
+#[derive(Debug, Clone)]
+pub struct Lab {
+    pub map: Grid<char>,
+    pub guard: (i16, i16),
+    pub direction: (i16, i16),
+}
 
 #[aoc_generator(day6)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn input_generator(input: &str) -> Result<Lab, AocError> {
+    let map = Grid::parse(6, input)?;
+    let (guard, ch) = map.iter()
+                         .find(|&(_, &value)| value != '.' && value != '#')
+                         .map(|(position, &value)| (position, value))
+                         .ok_or(AocError::MissingInput { day: 6, expected: "the guard" })?;
+
+    Ok(Lab {
+        direction: get_direction(&ch),
+        map,
+        guard,
+    })
 }
 
 #[aoc(day6, part1)]
-pub fn solve_part1(input: &[String]) -> i32 {
-    let map = create_map(input);
+pub fn solve_part1(lab: &Lab) -> i32 {
+    let map = &lab.map;
     let mut positions: HashSet<(i16, i16)> = HashSet::new();
-    let (mut position, ch) = map.iter()
-                            .find(|&(_, &value)| value != '.' && value != '#')
-                            .map(|(&position, &value)| (position, value))
-                            .unwrap();
-    let mut direction = get_direction(&ch);
+    let mut position = lab.guard;
+    let mut direction = lab.direction;
     positions.insert(position);
     
     loop {
         let next_position = (position.0 + direction.0, position.1 + direction.1);
     
-        if !map.contains_key(&next_position) {
-            break; 
-        }
-    
-        if let Some(&next_value) = map.get(&next_position) {
+        if let Some(&next_value) = map.get(next_position) {
             if next_value == '#' {
                 direction = rotate_90(direction); 
             } else {
                 position = next_position;
                 positions.insert(position);
             }
+        } else {
+            break;
         }
     }
     
@@ -41,14 +54,10 @@ pub fn solve_part1(input: &[String]) -> i32 {
 }
 
 #[aoc(day6, part2)]
-pub fn solve_part2(input: &[String]) -> i32 {
-    let mut map = create_map(input);
-    let (initial_pos, ch) = map
-        .iter()
-        .find(|&(_, &value)| value != '.' && value != '#')
-        .map(|(&position, &value)| (position, value))
-        .unwrap();
-    let initial_direction = get_direction(&ch);
+pub fn solve_part2(lab: &Lab) -> i32 {
+    let mut map = lab.map.clone();
+    let initial_pos = lab.guard;
+    let initial_direction = lab.direction;
 
     let mut visited_positions = HashSet::new();
     let mut position = initial_pos;
@@ -56,36 +65,28 @@ pub fn solve_part2(input: &[String]) -> i32 {
 
     loop {
         let next_position = (position.0 + direction.0, position.1 + direction.1);
-        if !map.contains_key(&next_position) {
-            break;
-        }
-        if let Some(&next_value) = map.get(&next_position) {
+        if let Some(&next_value) = map.get(next_position) {
             if next_value == '#' {
                 direction = rotate_90(direction);
             } else {
                 visited_positions.insert(position);
                 position = next_position;
             }
+        } else {
+            break;
         }
     }
 
     let neighbors: HashSet<(i16, i16)> = visited_positions
         .iter()
-        .flat_map(|&(x, y)| {
-            vec![
-                (x - 1, y),
-                (x + 1, y),
-                (x, y - 1),
-                (x, y + 1),
-            ]
-        })
-        .filter(|&pos| map.get(&pos) == Some(&'.') && pos != initial_pos)
+        .flat_map(|&position| map.neighbours4(position))
+        .filter(|&pos| map.get(pos) == Some(&'.') && pos != initial_pos)
         .collect();
 
     let mut valid_obstructions = HashSet::new();
 
     for obstruction in neighbors {
-        map.insert(obstruction, '#');
+        map[obstruction] = '#';
         let mut position = initial_pos;
         let mut direction = initial_direction;
         let mut visited_with_direction = HashSet::new();
@@ -93,10 +94,7 @@ pub fn solve_part2(input: &[String]) -> i32 {
 
         loop {
             let next_position = (position.0 + direction.0, position.1 + direction.1);
-            if !map.contains_key(&next_position) {
-                break;
-            }
-            if let Some(&next_value) = map.get(&next_position) {
+            if let Some(&next_value) = map.get(next_position) {
                 if next_value == '#' {
                     direction = rotate_90(direction);
                 } else {
@@ -106,6 +104,8 @@ pub fn solve_part2(input: &[String]) -> i32 {
                         break;
                     }
                 }
+            } else {
+                break;
             }
         }
 
@@ -113,23 +113,13 @@ pub fn solve_part2(input: &[String]) -> i32 {
             valid_obstructions.insert(obstruction);
         }
 
-        map.insert(obstruction, '.');
+        map[obstruction] = '.';
     }
 
     valid_obstructions.len() as i32
 }
 
 
-fn create_map(input: &[String]) -> HashMap<(i16, i16), char> {
-    let mut map = HashMap::new();
-    for (row, line) in input.iter().enumerate() {
-        for (col, ch) in line.chars().enumerate() {
-            map.insert(((row + 1) as i16, (col + 1) as i16), ch);
-        }
-    }
-    map
-}
-
 fn get_direction(value: &char) -> (i16, i16) {
     if *value == '>' {
         (0,1)
@@ -1,24 +1,37 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{AocError, Line};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub value: u64,
+    pub numbers: Vec<u64>,
+}
 
 #[aoc_generator(day7)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Vec<Equation>, AocError> {
+    input.lines()
+         .enumerate()
+         .map(|(index, text)| {
+            let line = Line::new(7, index, text);
+            let (first, rest) = text.split_once(':').ok_or(line.expected_end("`:` after the test value"))?;
+            let value = line.parse::<u64>(first)?;
+            let numbers = rest.split_whitespace()
+                              .map(|x| line.parse::<u64>(x))
+                              .collect::<Result<Vec<u64>, AocError>>()?;
+            if numbers.is_empty() {
+                return Err(line.expected_end("at least one number"));
+            }
+            Ok(Equation { value, numbers })
+         })
+         .collect()
 }
 
 #[aoc(day7, part1)]
-pub fn solve_part1(input: &[String]) -> u64 {
+pub fn solve_part1(input: &[Equation]) -> u64 {
     input.iter()
-         .map(|line| {
-            let (first, rest) = line.split_once(":").unwrap();
-            let number = first.trim().parse::<u64>().unwrap();
-            let vec = rest.split_whitespace()
-                          .map(|x| x.parse::<u64>().unwrap())
-                          .collect::<Vec<u64>>();
-            (number, vec)
-         })
-         .filter_map(|(number, vec)| {
-            if can_calculate(number, &vec) {
-                Some(number)
+         .filter_map(|equation| {
+            if can_calculate(equation.value, &equation.numbers) {
+                Some(equation.value)
             } else {
                 None
             }
@@ -27,30 +40,18 @@ pub fn solve_part1(input: &[String]) -> u64 {
 }
 
 #[aoc(day7, part2)]
-pub fn solve_part2(input: &[String]) -> u64 {
-    
+pub fn solve_part2(input: &[Equation]) -> u64 {
     input.iter()
-         .map(|line| {
-            let (first, rest) = line.split_once(":").unwrap();
-            let number = first.trim().parse::<u64>().unwrap();
-            let vec = rest.split_whitespace()
-                          .map(|x| x.parse::<u64>().unwrap())
-                          .collect::<Vec<u64>>();
-            (number, vec)
-         })
-         .filter_map(|(number, vec)| {
-            if can_calculate(number, &vec) {
-                Some(number)
+         .filter_map(|equation| {
+            if can_calculate(equation.value, &equation.numbers)
+                || can_calculate_concat(equation.value, &equation.numbers)
+            {
+                Some(equation.value)
             } else {
-                if can_calculate_concat(number, &vec) {
-                    Some(number)
-                } else {
-                    None
-                }
+                None
             }
          })
          .sum::<u64>()
-    
 }
 
 
@@ -63,7 +64,7 @@ fn can_calculate(value: u64, vec: &[u64]) -> bool {
     let num = vec[vec.len()-1];
     let mut result = false;
 
-    if value % num == 0 {
+    if value.is_multiple_of(num) {
         result |= can_calculate(value/num, &vec[..vec.len()-1])
     } 
     
@@ -83,7 +84,7 @@ fn can_calculate_concat(value: u64, vec: &[u64]) -> bool {
     let mut result = false;
 
     //division
-    if value % num == 0 {
+    if value.is_multiple_of(num) {
         result |= can_calculate_concat(value / num, &vec[..vec.len() - 1]);
     }
 
@@ -1,34 +1,41 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use crate::error::AocError;
+use crate::utils::Grid;
+
+#[derive(Debug, Clone)]
+pub struct Antennas {
+    pub map: Grid<char>,
+    pub frequencies: HashMap<char, Vec<(i16, i16)>>,
+}
+
+impl Antennas {
+    fn contains(&self, position: (i16, i16)) -> bool {
+        self.map.contains(position)
+    }
+}
 
 #[aoc_generator(day8)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Antennas, AocError> {
+    let map = Grid::parse(8, input)?;
+    let mut frequencies: HashMap<char, Vec<(i16, i16)>> = HashMap::new();
+
+    for (position, &ch) in map.iter() {
+        if ch != '.' {
+            frequencies.entry(ch).or_default().push(position);
+        }
+    }
+
+    Ok(Antennas { map, frequencies })
 }
 
 #[aoc(day8, part1)]
-pub fn solve_part1(input: &[String]) -> usize {
-
-    //create map
-    let map = create_map(input);
+pub fn solve_part1(input: &Antennas) -> usize {
     let mut antinodes: HashSet<(i16, i16)> = HashSet::new();
-    //distinct chars
-    let frequencies: HashSet<char> = map.values().copied().collect();
-    
-    let min_x = map.keys().map(|&(x, _)| x).min().unwrap();
-    let max_x = map.keys().map(|&(x, _)| x).max().unwrap();
-    let min_y = map.keys().map(|&(_, y)| y).min().unwrap();
-    let max_y = map.keys().map(|&(_, y)| y).max().unwrap();
-
-    //iterate each char
-    for &frequency in &frequencies{
-
-        //get all positions with this frequency
-        let antennas: Vec<_> = map.iter()
-                                  .filter_map(|(&pos, &ch)| if ch == frequency  && ch != '.' { Some(pos) } else { None })
-                                  .collect();
-        
+
+    //iterate each frequency
+    for antennas in input.frequencies.values() {
         for i in 0..antennas.len(){
             for j in i + 1..antennas.len(){
                 let a1 = antennas[i];
@@ -43,7 +50,7 @@ pub fn solve_part1(input: &[String]) -> usize {
                     continue;
                 }
                 //direction vector
-                let gcd = gcd(dx.abs() as i16, dy.abs() as i16);
+                let gcd = gcd(dx.abs(), dy.abs());
                 let ux = dx / gcd;
                 let uy = dy / gcd;
                 
@@ -52,60 +59,28 @@ pub fn solve_part1(input: &[String]) -> usize {
                 let antinode2 = (a2.0 + ux, a2.1 + uy);
                 
                 //check limit and add node
-                if antinode1.0 >= min_x && antinode1.0 <= max_x && antinode1.1 >= min_y && antinode1.1 <= max_y {
+                if input.contains(antinode1) {
                     antinodes.insert(antinode1);
                 }
-                if antinode2.0 >= min_x && antinode2.0 <= max_x && antinode2.1 >= min_y && antinode2.1 <= max_y {
+                if input.contains(antinode2) {
                     antinodes.insert(antinode2);
                 }
             }
-
         }
-        
-            
-
     }
 
-    //print antinodes each char in it's position
-    // Print the map
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            if antinodes.contains(&(y, x)) {
-                print!("#");
-            } else if let Some(&ch) = map.get(&(y, x)) {
-                print!("{}", ch);
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
+    print_antinodes(input, &antinodes);
 
     //count 
     antinodes.len()
 }
 
 #[aoc(day8, part2)]
-pub fn solve_part2(input: &[String]) -> usize {
-    //create map
-    let map = create_map(input);
+pub fn solve_part2(input: &Antennas) -> usize {
     let mut antinodes: HashSet<(i16, i16)> = HashSet::new();
-    //distinct chars
-    let frequencies: HashSet<char> = map.values().copied().collect();
-    
-    let min_x = map.keys().map(|&(x, _)| x).min().unwrap();
-    let max_x = map.keys().map(|&(x, _)| x).max().unwrap();
-    let min_y = map.keys().map(|&(_, y)| y).min().unwrap();
-    let max_y = map.keys().map(|&(_, y)| y).max().unwrap();
-
-    //iterate each char
-    for &frequency in &frequencies{
-
-        //get all positions with this frequency
-        let antennas: Vec<_> = map.iter()
-                                  .filter_map(|(&pos, &ch)| if ch == frequency  && ch != '.' { Some(pos) } else { None })
-                                  .collect();
-        
+
+    //iterate each frequency
+    for antennas in input.frequencies.values() {
         for i in 0..antennas.len(){
             for j in i + 1..antennas.len(){
                 
@@ -117,58 +92,42 @@ pub fn solve_part2(input: &[String]) -> usize {
                 let dy = a2.1 - a1.1;
 
                 //use the gds divisor to normalize the vector
-                let gcd = gcd(dx.abs() as i16, dy.abs() as i16);
+                let gcd = gcd(dx.abs(), dy.abs());
                 let ux = dx / gcd;
                 let uy = dy / gcd;
 
                 //extend the line backwards to the edge of the map
                 let mut current = (a1.0, a1.1);
-                while current.0 >= min_x && current.0 <= max_x && current.1 >= min_y && current.1 <= max_y {
+                while input.contains(current) {
                     antinodes.insert(current);
                     current = (current.0 - ux, current.1 - uy);
                 }
 
                 //extend the line forward to the edge of the map
                 current = (a2.0, a2.1);
-                while current.0 >= min_x && current.0 <= max_x && current.1 >= min_y && current.1 <= max_y {
+                while input.contains(current) {
                     antinodes.insert(current);
                     current = (current.0 + ux, current.1 + uy);
 
                 }
             }
         }
-        
-            
-
     }
 
-    //print antinodes each char in it's position
-    // Print the map
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            if antinodes.contains(&(y, x)) {
-                print!("#");
-            } else if let Some(&ch) = map.get(&(y, x)) {
-                print!("{}", ch);
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
+    print_antinodes(input, &antinodes);
 
     //count 
     antinodes.len()
 }
 
-fn create_map(input: &[String]) -> HashMap<(i16, i16), char> {
-    let mut map = HashMap::new();
-    for (row, line) in input.iter().enumerate() {
-        for (col, ch) in line.chars().enumerate() {
-            map.insert(((row) as i16, (col) as i16), ch);
-        }
+//print antinodes each char in it's position
+fn print_antinodes(input: &Antennas, antinodes: &HashSet<(i16, i16)>) {
+    let mut map = input.map.clone();
+    for &antinode in antinodes {
+        map[antinode] = '#';
     }
-    map
+
+    print!("{}", map);
 }
 
 fn gcd(mut a: i16, mut b: i16) -> i16 {
@@ -1,155 +1,323 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{AocError, Line};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 
+//the disk map, one digit per file or free space
 #[aoc_generator(day9)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Vec<u32>, AocError> {
+    let text = input.lines().next().unwrap_or_default();
+    let line = Line::new(9, 0, text);
+
+    text.char_indices()
+        .map(|(column, ch)| ch.to_digit(10).ok_or(line.expected(&text[column..], "a digit")))
+        .collect()
 }
 
 #[aoc(day9, part1)]
-pub fn solve_part1(input: &[String]) -> i64 {
+pub fn solve_part1(input: &[u32]) -> i64 {
     //represent disk map has diskmap layout
-    let mut disk_layout = extract_disk_layout(&input[0]);
-    
-    disk_layout = organize_disk(disk_layout);
-    calculate_checksum(disk_layout)
+    let disk_layout = extract_disk_layout(input);
+    calculate_checksum(&organize_disk(&disk_layout))
 }
 
 #[aoc(day9, part2)]
-pub fn solve_part2(input: &[String]) -> i64 {
-    let mut disk_layout = extract_disk_layout(&input[0]);
-    disk_layout = organize_disk_by_file(disk_layout);
-    calculate_checksum(disk_layout)
+pub fn solve_part2(input: &[u32]) -> i64 {
+    let disk_layout = extract_disk_layout(input);
+    calculate_checksum(&organize_disk_by_file(&disk_layout))
 }
 
-fn organize_disk(mut disk_layout: Vec<String>) -> Vec<String> {
+//consecutive blocks, of one file or free
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub len: usize,
+}
 
-    //while we have a '.' between the numbers - that is a . with a number on its right
-    while disk_layout.windows(2).any(|pair| pair[0] == "." && pair[1].parse::<u32>().is_ok()) {
+//a whole file, or the part of one that moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct File {
+    pub id: u32,
+    pub span: Span,
+}
 
-        //iterate over the disk_layout finding files from right to left
-        for i in (0..disk_layout.len()).rev() {
-            if let Ok(_) = disk_layout[i].parse::<u32>() {
-                //find the first available space from left to right
-                if let Some(j) = disk_layout.iter().position(|ch| ch == ".") {
-                    //move the file to the first available space
-                    disk_layout.swap(i, j);
-                    break;
-                }
+//the disk as spans, the blocks themselves are never expanded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    //sorted by position
+    pub files: Vec<File>,
+    //the gaps between the files, up to the end of the disk
+    pub free: Vec<Span>,
+    pub size: usize,
+}
+
+impl Disk {
+    //empty files take no blocks and are dropped
+    pub fn new(mut files: Vec<File>, size: usize) -> Self {
+        files.retain(|file| file.span.len > 0);
+        files.sort_by_key(|file| file.span.start);
+
+        let mut free = Vec::new();
+        let mut position = 0;
+        for file in &files {
+            if file.span.start > position {
+                free.push(Span { start: position, len: file.span.start - position });
             }
+            position = file.span.start + file.span.len;
+        }
+        if size > position {
+            free.push(Span { start: position, len: size - position });
         }
+
+        Disk { files, free, size }
     }
 
-    disk_layout
-}
+    //back to the digits of the puzzle, alternating file and free runs.
+    //the file IDs are only implied by the order, runs longer than 9 are
+    //split by empty runs of the other kind
+    pub fn disk_map(&self) -> String {
+        let mut digits = String::new();
+        let mut position = 0;
 
-fn organize_disk_by_file(mut disk_layout: Vec<String>) -> Vec<String> {
-    //1 - get files (ID, size, starting position), sorted by file ID descending
-    let mut files: Vec<(u32, usize, usize)> = Vec::new();
-    let mut current_id = None;
-    let mut current_size = 0;
-    let mut start_pos = 0;
-
-    for (i, block) in disk_layout.iter().enumerate() {
-        if let Ok(file_id) = block.parse::<u32>() {
-            if current_id == Some(file_id) {
-                current_size += 1;
-            } else {
-                if let Some(id) = current_id {
-                    files.push((id, current_size, start_pos));
+        for (index, file) in self.files.iter().enumerate() {
+            if index > 0 || file.span.start > 0 {
+                if index == 0 {
+                    digits.push('0');
                 }
-                current_id = Some(file_id);
-                current_size = 1;
-                start_pos = i;
+                push_run(&mut digits, file.span.start - position);
             }
-        } else {
-            if let Some(id) = current_id {
-                files.push((id, current_size, start_pos));
+            push_run(&mut digits, file.span.len);
+            position = file.span.start + file.span.len;
+        }
+        if self.size > position {
+            if self.files.is_empty() {
+                digits.push('0');
             }
-            current_id = None;
-            current_size = 0;
+            push_run(&mut digits, self.size - position);
+        }
+
+        digits
+    }
+
+    //one character per block as in the puzzle, the ID of the file or `.`
+    pub fn blocks(&self) -> String {
+        let mut blocks = String::new();
+        let mut position = 0;
+        for file in &self.files {
+            blocks.push_str(&".".repeat(file.span.start - position));
+            blocks.push_str(&file.id.to_string().repeat(file.span.len));
+            position = file.span.start + file.span.len;
         }
+        blocks.push_str(&".".repeat(self.size - position));
+        blocks
     }
-    if let Some(id) = current_id {
-        files.push((id, current_size, start_pos));
+}
+
+fn push_run(digits: &mut String, mut len: usize) {
+    while len > 9 {
+        digits.push_str("90");
+        len -= 9;
     }
-    files.sort_by(|a, b| b.0.cmp(&a.0)); //sort by file ID descending
+    digits.push(char::from_digit(len as u32, 10).unwrap());
+}
 
-    //2 - get free spaces (size, starting position), sorted by position
-    let mut spaces: Vec<(usize, usize)> = Vec::new();
-    let mut space_size = 0;
-    let mut space_start = 0;
+//how scattered a compacted disk is, compared to the disk before compaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragmentation {
+    //files stored in more than one span
+    pub split_files: usize,
+    pub largest_free: usize,
+    //files still in their original place, in ID order
+    pub unmoved: Vec<u32>,
+}
 
-    for (i, block) in disk_layout.iter().enumerate() {
-        if block == "." {
-            if space_size == 0 {
-                space_start = i;
-            }
-            space_size += 1;
-        } else {
-            if space_size > 0 {
-                spaces.push((space_size, space_start));
-                space_size = 0;
-            }
+pub fn fragmentation(before: &Disk, after: &Disk) -> Fragmentation {
+    let mut spans: HashMap<u32, Vec<Span>> = HashMap::new();
+    for file in &after.files {
+        spans.entry(file.id).or_default().push(file.span);
+    }
+
+    let mut unmoved: Vec<u32> = before
+        .files
+        .iter()
+        .filter(|file| spans.get(&file.id) == Some(&vec![file.span]))
+        .map(|file| file.id)
+        .collect();
+    unmoved.sort_unstable();
+
+    Fragmentation {
+        split_files: spans.values().filter(|spans| spans.len() > 1).count(),
+        largest_free: after.free.iter().map(|gap| gap.len).max().unwrap_or(0),
+        unmoved,
+    }
+}
+
+//even digits are files, numbered from 0, odd digits the free space after them
+fn extract_disk_layout(input: &[u32]) -> Disk {
+    let mut files = Vec::new();
+    let mut position = 0;
+
+    for (pos, &num) in input.iter().enumerate() {
+        let len = num as usize;
+        if pos % 2 == 0 && len > 0 {
+            files.push(File { id: (pos / 2) as u32, span: Span { start: position, len } });
+        }
+        position += len;
+    }
+
+    Disk::new(files, position)
+}
+
+//move single blocks from the end of the disk into the leftmost free block,
+//splitting files when a gap is too small for the rest of them
+fn organize_disk(disk: &Disk) -> Disk {
+    let mut files = disk.files.clone();
+    let mut moved = Vec::new();
+    let mut gaps = disk.free.iter().copied().filter(|gap| gap.len > 0);
+    let mut gap = gaps.next();
+
+    while let Some(mut free) = gap {
+        let Some(mut last) = files.pop() else {
+            break;
+        };
+        if free.start >= last.span.start {
+            files.push(last);
+            break;
+        }
+
+        //the tail of the file fills the head of the gap
+        let len = free.len.min(last.span.len);
+        moved.push(File { id: last.id, span: Span { start: free.start, len } });
+        last.span.len -= len;
+        free.start += len;
+        free.len -= len;
+
+        if last.span.len > 0 {
+            files.push(last);
         }
+        gap = if free.len > 0 { Some(free) } else { gaps.next() };
     }
-    if space_size > 0 {
-        spaces.push((space_size, space_start));
+
+    files.extend(moved);
+    Disk::new(files, disk.size)
+}
+
+//move whole files, highest ID first, into the leftmost gap they fit in.
+//gaps are kept in one heap per size so the leftmost fitting gap is found
+//by looking at the top of each bigger heap
+fn organize_disk_by_file(disk: &Disk) -> Disk {
+    let largest = disk.free.iter().map(|gap| gap.len).max().unwrap_or(0);
+    let mut buckets: Vec<BinaryHeap<Reverse<usize>>> = vec![BinaryHeap::new(); largest + 1];
+    for gap in &disk.free {
+        buckets[gap.len].push(Reverse(gap.start));
     }
 
-    //3 - move files to the leftmost valid space
-    for (file_id, file_size, file_pos) in files {
-        if let Some((space_index, &(_, space_pos))) = spaces
+    let mut files = disk.files.clone();
+    for file in files.iter_mut().rev() {
+        let len = file.span.len;
+        let leftmost = buckets
             .iter()
             .enumerate()
-            .find(|&(_, &(space_size, space_pos))| space_size >= file_size && space_pos < file_pos)
-        {
-            //move the file to the new space
-            for i in 0..file_size {
-                disk_layout[space_pos + i] = file_id.to_string();
-            }
-            //clear the original file position
-            for i in 0..file_size {
-                disk_layout[file_pos + i] = ".".to_string();
-            }
-            //update the free space list
-            let remaining_space = spaces[space_index].0 - file_size;
-            if remaining_space > 0 {
-                spaces[space_index] = (remaining_space, space_pos + file_size);
-            } else {
-                spaces.remove(space_index);
-            }
+            .skip(len)
+            .filter_map(|(size, bucket)| bucket.peek().map(|&Reverse(start)| (start, size)))
+            .min();
+
+        //files only ever move to the left
+        let Some((start, size)) = leftmost.filter(|&(start, _)| start < file.span.start) else {
+            continue;
+        };
+        buckets[size].pop();
+        if size > len {
+            buckets[size - len].push(Reverse(start + len));
         }
+        file.span.start = start;
     }
 
-    disk_layout
+    Disk::new(files, disk.size)
 }
 
-
-fn extract_disk_layout(input: &str) -> Vec<String> {
-    let mut prev_num = 0;
-    input
-        .char_indices()
-        .flat_map(|(pos, ch)| {
-            ch.to_digit(10).map(|num| {
-                if pos % 2 == 0 {
-                    //if even it is a file
-                    let result = (0..num).map(|_| prev_num.to_string()).collect::<Vec<String>>();
-                    prev_num += 1; //increment after adding the file blocks
-                    result
-                } else {
-                    //if odd it is free space
-                    vec![".".to_string(); num as usize]
-                }
-            }).unwrap_or_else(Vec::new)
+fn calculate_checksum(disk_layout: &Disk) -> i64 {
+    disk_layout
+        .files
+        .iter()
+        .filter(|file| file.span.len > 0)
+        .map(|file| {
+            //sum of the positions the file covers
+            let Span { start, len } = file.span;
+            let positions = (start * 2 + len - 1) * len / 2;
+            positions as i64 * file.id as i64
         })
-        .collect()
+        .sum()
 }
 
-fn calculate_checksum(disk_layout: Vec<String>) -> i64 {
-    disk_layout
-        .into_iter()
-        .enumerate()
-        .filter(|(_, c)| *c != ".")
-        .map(|(i, c)| i as i64 * c.parse::<u64>().unwrap() as i64)
-        .sum()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day09_part1() {
+        let input = generate_input("2333133121414131402").unwrap();
+        assert_eq!(solve_part1(&input), 1928);
+    }
+
+    #[test]
+    fn test_day09_part2() {
+        let input = generate_input("2333133121414131402").unwrap();
+        assert_eq!(solve_part2(&input), 2858);
+    }
+
+    #[test]
+    fn test_day09_layouts() {
+        let input = generate_input("2333133121414131402").unwrap();
+        let disk = extract_disk_layout(&input);
+        assert_eq!(disk.blocks(), "00...111...2...333.44.5555.6666.777.888899");
+        assert_eq!(disk.disk_map(), "2333133121414131402");
+
+        let compacted = organize_disk(&disk);
+        assert_eq!(compacted.blocks(), "0099811188827773336446555566..............");
+        assert_eq!(
+            fragmentation(&disk, &compacted),
+            Fragmentation { split_files: 2, largest_free: 14, unmoved: vec![0, 1, 2, 3, 4, 5] }
+        );
+
+        let by_file = organize_disk_by_file(&disk);
+        assert_eq!(by_file.blocks(), "00992111777.44.333....5555.6666.....8888..");
+        assert_eq!(by_file.disk_map(), "20201030312134414542");
+        assert_eq!(
+            fragmentation(&disk, &by_file),
+            Fragmentation { split_files: 0, largest_free: 5, unmoved: vec![0, 1, 3, 5, 6, 8] }
+        );
+
+        //the digits read back into the same blocks, with the files numbered in order
+        let reread = extract_disk_layout(&generate_input(&by_file.disk_map()).unwrap());
+        assert_eq!(reread.free, by_file.free);
+        assert_eq!(reread.blocks(), "00112333444.55.666....7777.8888.....9999..");
+
+        let long = Disk::new(vec![File { id: 0, span: Span { start: 12, len: 11 } }], 25);
+        assert_eq!(long.disk_map(), "09039022");
+        assert_eq!(extract_disk_layout(&generate_input(&long.disk_map()).unwrap()).free, long.free);
+
+        let empty = Disk::new(
+            vec![File { id: 1, span: Span { start: 0, len: 0 } }, File { id: 2, span: Span { start: 1, len: 2 } }],
+            3,
+        );
+        assert_eq!(empty.files.len(), 1);
+        assert_eq!(calculate_checksum(&empty), 6);
+        let unchecked = Disk { files: vec![File { id: 1, span: Span { start: 0, len: 0 } }], free: Vec::new(), size: 0 };
+        assert_eq!(calculate_checksum(&unchecked), 0);
+    }
+
+    #[test]
+    fn test_day09_large_disk() {
+        //two million blocks, every file ends up packed at the start
+        let input: Vec<u32> = std::iter::repeat_n([5, 5], 200_000).flatten().collect();
+        let disk = extract_disk_layout(&input);
+        assert_eq!(disk.size, 2_000_000);
+
+        let compacted = organize_disk(&disk);
+        assert_eq!(compacted.free, vec![Span { start: 1_000_000, len: 1_000_000 }]);
+        let by_file = organize_disk_by_file(&disk);
+        assert_eq!(by_file.free, compacted.free);
+        assert_eq!(calculate_checksum(&by_file), calculate_checksum(&compacted));
+    }
 }
@@ -1,26 +1,21 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use crate::utils::create_map;
+use crate::error::AocError;
+use crate::utils::Grid;
 use std::collections::VecDeque;
 use std::collections::HashSet;
 
 
+//height of each position of the topographic map
 #[aoc_generator(day10)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Grid<u32>, AocError> {
+    Grid::parse_with(10, input, "a height from 0 to 9", |ch| ch.to_digit(10))
 }
 
 #[aoc(day10, part1)]
-pub fn solve_part1(input: &[String]) -> i32 {
-    let map = create_map(input);
+pub fn solve_part1(map: &Grid<u32>) -> i32 {
     
     //find all trailheads
-    let trailheads = map.iter()
-                        .filter(|&(_, &value)| value == '0')
-                        .map(|(&position, _)| position)
-                        .collect::<Vec<(i16, i16)>>();
-
-    //can only move horizontally or vertically
-    let directions = vec![(0, 1), (1, 0), (0, -1), (-1, 0)];
+    let trailheads = map.find_all(&0).collect::<Vec<(i16, i16)>>();
     
     //use BFS to explore all possible paths from trailhead
     let mut total = 0;
@@ -41,20 +36,17 @@ pub fn solve_part1(input: &[String]) -> i32 {
             visited.insert(current);
 
             //check if current position is a peak
-            if let Some(&value) = map.get(&current){
-                if value == '9'{
+            if let Some(&value) = map.get(current){
+                if value == 9{
                     reachable_peaks += 1;
                     continue; //no need to explore further
                 }
             }
 
-            //explore all possible directions
-            for &(dx, dy) in &directions{
-                let next = (current.0 + dx, current.1 + dy);
-                if let Some(&next_value) = map.get(&next){
-                    if next_value.to_digit(10) == Some(height +1) {
-                        queue.push_back((next, height + 1));
-                    }
+            //explore all possible directions, can only move horizontally or vertically
+            for next in map.neighbours4(current){
+                if map[next] == height + 1 {
+                    queue.push_back((next, height + 1));
                 }
             }
         }
@@ -64,18 +56,9 @@ pub fn solve_part1(input: &[String]) -> i32 {
 }
 
 #[aoc(day10, part2)]
-pub fn solve_part2(input: &[String]) -> i32 {
-    let map = create_map(input);
-
+pub fn solve_part2(map: &Grid<u32>) -> i32 {
     //find the trailheads
-    let trailheads = map
-        .iter()
-        .filter(|&(_, &value)| value == '0')
-        .map(|(&position, _)| position)
-        .collect::<Vec<(i16, i16)>>();
-
-    //movements
-    let directions = vec![(0, 1), (1, 0), (0, -1), (-1, 0)];
+    let trailheads = map.find_all(&0).collect::<Vec<(i16, i16)>>();
 
     let mut total_rating = 0;
 
@@ -88,21 +71,18 @@ pub fn solve_part2(input: &[String]) -> i32 {
 
         while let Some((current, height, path)) = queue.pop_front() {
             
-            if let Some(&value) = map.get(&current) {
-                if value == '9' {
+            if let Some(&value) = map.get(current) {
+                if value == 9 {
                     trails.insert(path.clone()); 
                     continue;
                 }
 
                 
-                for &(dx, dy) in &directions {
-                    let next = (current.0 + dx, current.1 + dy);
-                    if let Some(&next_value) = map.get(&next) {
-                        if next_value.to_digit(10) == Some(height + 1) {
-                            let mut new_path = path.clone();
-                            new_path.push(next); 
-                            queue.push_back((next, height + 1, new_path));
-                        }
+                for next in map.neighbours4(current) {
+                    if map[next] == height + 1 {
+                        let mut new_path = path.clone();
+                        new_path.push(next); 
+                        queue.push_back((next, height + 1, new_path));
                     }
                 }
             }
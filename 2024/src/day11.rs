@@ -1,80 +1,175 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use dashmap::DashMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use crate::error::{AocError, Line};
 
 
 #[aoc_generator(day11)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Vec<u64>, AocError> {
+    let text = input.lines().next().ok_or(AocError::MissingInput { day: 11, expected: "the stones" })?;
+    let line = Line::new(11, 0, text);
+
+    text.split_whitespace().map(|x| line.parse::<u64>(x)).collect()
 }
 
-#[aoc(day11, part1)]
-pub fn solve_part1(input: &[String]) -> usize {
-    let mut stones = input[0].split_whitespace().map(|x| x.parse::<u64>().unwrap()).collect::<Vec<u64>>();
-    for _ in 0..25 {
-        stones = stones.iter().flat_map(|x| 
-            if *x == 0 {
-                vec![process_zero(*x)]
-            } else if is_even_digits(*x) {
-                let (left, right) = split_stones(*x);
+//one way a stone can change when blinking, the closures may capture their parameters
+#[derive(Clone)]
+pub struct Rule {
+    pub applies: Arc<dyn Fn(u64) -> bool + Send + Sync>,
+    //None when the new stones can't be numbered
+    pub transform: Arc<dyn Fn(u64) -> Option<Vec<u64>> + Send + Sync>,
+}
+
+impl Rule {
+    pub fn new(
+        applies: impl Fn(u64) -> bool + Send + Sync + 'static,
+        transform: impl Fn(u64) -> Vec<u64> + Send + Sync + 'static,
+    ) -> Self {
+        Rule::checked(applies, move |stone| Some(transform(stone)))
+    }
+
+    pub fn checked(
+        applies: impl Fn(u64) -> bool + Send + Sync + 'static,
+        transform: impl Fn(u64) -> Option<Vec<u64>> + Send + Sync + 'static,
+    ) -> Self {
+        Rule { applies: Arc::new(applies), transform: Arc::new(transform) }
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rule").finish_non_exhaustive()
+    }
+}
+
+//the first rule that applies to a stone changes it, stones no rule applies to stay as they are
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        RuleSet { rules }
+    }
+
+    //the rules of the puzzle
+    pub fn standard() -> Self {
+        RuleSet::new(vec![
+            Rule::new(|stone| stone == 0, |stone| vec![process_zero(stone)]),
+            Rule::new(is_even_digits, |stone| {
+                let (left, right) = split_stones(stone);
                 vec![left, right]
-            } else {
-                vec![process_oder(*x)]
-            })
-        .collect();
+            }),
+            Rule::checked(|_| true, |stone| process_oder(stone).map(|next| vec![next])),
+        ])
     }
-    stones.len()
+
+    pub fn apply(&self, stone: u64) -> Option<Vec<u64>> {
+        match self.rules.iter().find(|rule| (rule.applies)(stone)) {
+            Some(rule) => (rule.transform)(stone),
+            None => Some(vec![stone]),
+        }
+    }
+}
+
+#[aoc(day11, part1)]
+pub fn solve_part1(input: &[u64]) -> Result<u128, AocError> {
+    count_all(input, 25, &RuleSet::standard())
 }
 
 #[aoc(day11, part2)]
-pub fn solve_part2(input: &[String]) -> usize {
-    let initial_stones: Vec<u64> = input[0]
-        .split_whitespace()
-        .map(|x| x.parse::<u64>().unwrap())
-        .collect();
+pub fn solve_part2(input: &[u64]) -> Result<u128, AocError> {
+    count_all(input, 75, &RuleSet::standard())
+}
 
-    let cache: DashMap<(u64, usize), usize> = DashMap::new();
-    let total_blinks = 75;
+pub fn count_all(input: &[u64], total_blinks: usize, rules: &RuleSet) -> Result<u128, AocError> {
+    let mut stones = Stones::new(input);
+    for _ in 0..total_blinks {
+        stones.blink(rules)?;
+    }
 
-    let total_length: usize = initial_stones
-        .into_iter()
-        .map(|stone| count_stones(stone, 0, total_blinks, &cache))
-        .sum();
+    stones.distribution().total.ok_or(AocError::Unsolvable {
+        day: 11,
+        reason: "there are more stones than fit in 128 bits",
+    })
+}
 
-    total_length
+//the line of stones after some blinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Distribution {
+    pub blinks: usize,
+    pub distinct: usize,
+    pub largest: Option<u64>,
+    //None once a count no longer fits
+    pub total: Option<u128>,
 }
 
-fn count_stones(
-    stone: u64,
-    current_blink: usize,
-    total_blinks: usize,
-    cache: &DashMap<(u64, usize), usize>,
-) -> usize {
-    if current_blink >= total_blinks {
-        return 1;
+//how many stones of each number there are, their order never matters
+#[derive(Debug, Clone)]
+pub struct Stones {
+    counts: HashMap<u64, u128>,
+    blinks: usize,
+    overflowed: bool,
+}
+
+impl Stones {
+    pub fn new(input: &[u64]) -> Self {
+        let mut stones = Stones { counts: HashMap::new(), blinks: 0, overflowed: false };
+        for &stone in input {
+            stones.add(stone, 1);
+        }
+        stones
     }
 
-    //check cache 
-    if let Some(cached_count) = cache.get(&(stone, current_blink)) {
-        return *cached_count;
+    //counts saturate instead of wrapping around
+    fn add(&mut self, stone: u64, count: u128) {
+        let total = self.counts.entry(stone).or_insert(0);
+        *total = total.checked_add(count).unwrap_or_else(|| {
+            self.overflowed = true;
+            u128::MAX
+        });
     }
 
-    let count = if stone == 0 {
-        count_stones(1, current_blink + 1, total_blinks, cache)
-    } else if !is_even_digits(stone) {
-        count_stones(stone * 2024, current_blink + 1, total_blinks, cache)
-    } else {
-        let (left, right) = split_stones(stone);
-        let left_count = count_stones(left, current_blink + 1, total_blinks, cache);
-        let right_count = count_stones(right, current_blink + 1, total_blinks, cache);
-        left_count + right_count
-    };
+    pub fn count(&self, stone: u64) -> u128 {
+        self.counts.get(&stone).copied().unwrap_or(0)
+    }
 
-    //update cache
-    cache.insert((stone, current_blink), count);
+    //the stones are left as they were when one of them can't change
+    pub fn blink(&mut self, rules: &RuleSet) -> Result<Distribution, AocError> {
+        let changed: Vec<(Vec<u64>, u128)> = self
+            .counts
+            .iter()
+            .map(|(&stone, &count)| rules.apply(stone).map(|next| (next, count)))
+            .collect::<Option<_>>()
+            .ok_or(AocError::Unsolvable { day: 11, reason: "a stone number no longer fits in 64 bits" })?;
 
-    count
-}
+        self.counts.clear();
+        for (next, count) in changed {
+            for stone in next {
+                self.add(stone, count);
+            }
+        }
+        self.blinks += 1;
+        Ok(self.distribution())
+    }
+
+    pub fn distribution(&self) -> Distribution {
+        let total = self
+            .counts
+            .values()
+            .try_fold(0u128, |total, &count| total.checked_add(count))
+            .filter(|_| !self.overflowed);
 
+        Distribution {
+            blinks: self.blinks,
+            distinct: self.counts.len(),
+            largest: self.counts.keys().max().copied(),
+            total,
+        }
+    }
+}
 
 fn process_zero(stone: u64) -> u64 {
     stone + 1
@@ -88,7 +183,7 @@ fn number_digits(stone: u64) -> u64 {
     }
 }
 fn is_even_digits(stone: u64) -> bool {
-    number_digits(stone) % 2 == 0
+    number_digits(stone).is_multiple_of(2)
 }
 
 fn split_stones(stone: u64) -> (u64, u64) {
@@ -99,6 +194,83 @@ fn split_stones(stone: u64) -> (u64, u64) {
     (stone/divisor, stone%divisor)
 }
 
-fn process_oder(stone: u64) -> u64 {
-    stone * 2024
-}
\ No newline at end of file
+fn process_oder(stone: u64) -> Option<u64> {
+    stone.checked_mul(2024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day11_part1() {
+        let input = generate_input("125 17").unwrap();
+        assert_eq!(solve_part1(&input), Ok(55312));
+        assert_eq!(count_all(&input, 6, &RuleSet::standard()), Ok(22));
+    }
+
+    #[test]
+    fn test_day11_rule_sets() {
+        //odd stones split in three, even stones are halved
+        let rules = RuleSet::new(vec![
+            Rule::new(|stone| stone % 2 == 1, |stone| vec![stone / 3, stone / 3 + 1, 0]),
+            Rule::new(|stone| stone > 0, |stone| vec![stone / 2]),
+        ]);
+        assert_eq!(rules.apply(7), Some(vec![2, 3, 0]));
+        assert_eq!(rules.apply(0), Some(vec![0]));
+
+        let mut stones = vec![41, 8];
+        for _ in 0..10 {
+            stones = stones.iter().flat_map(|&stone| rules.apply(stone).unwrap()).collect();
+        }
+        assert_eq!(count_all(&[41, 8], 10, &rules), Ok(stones.len() as u128));
+
+        assert_eq!(count_all(&[1, 2, 3], 5, &RuleSet::new(Vec::new())), Ok(3));
+
+        //rules built at runtime from a parameter
+        let multiplied = |multiplier: u64| {
+            RuleSet::new(vec![
+                Rule::new(move |stone| stone >= multiplier, |stone| vec![stone / 10, stone % 10]),
+                Rule::new(|_| true, move |stone| vec![stone * multiplier]),
+            ])
+        };
+        assert_eq!(multiplied(3).apply(2), Some(vec![6]));
+        assert_eq!(multiplied(7).apply(2), Some(vec![14]));
+        assert_eq!(multiplied(7).apply(14), Some(vec![1, 4]));
+        assert_eq!(count_all(&[1], 3, &multiplied(3)), Ok(3));
+        assert_eq!(count_all(&[1], 3, &multiplied(12)), Ok(2));
+    }
+
+    #[test]
+    fn test_day11_distribution() {
+        let rules = RuleSet::standard();
+        let mut stones = Stones::new(&[125, 17]);
+        assert_eq!(
+            stones.blink(&rules),
+            Ok(Distribution { blinks: 1, distinct: 3, largest: Some(253000), total: Some(3) })
+        );
+        assert_eq!(stones.count(7), 1);
+
+        //the same stones keep coming back, only their counts grow
+        let mut stones = Stones::new(&[0]);
+        let mut distribution = stones.distribution();
+        for _ in 0..2000 {
+            distribution = stones.blink(&rules).unwrap();
+        }
+        assert_eq!(distribution.blinks, 2000);
+        assert!(distribution.distinct < 100);
+        assert_eq!(distribution.total, None);
+        assert!(count_all(&[0], 2000, &rules).is_err());
+    }
+
+    #[test]
+    fn test_day11_number_overflow() {
+        let error = AocError::Unsolvable { day: 11, reason: "a stone number no longer fits in 64 bits" };
+        assert_eq!(count_all(&[1_000_000_000_000_000_000], 3, &RuleSet::standard()), Err(error.clone()));
+
+        let mut stones = Stones::new(&[7, 1_000_000_000_000_000_000]);
+        assert_eq!(stones.blink(&RuleSet::standard()), Err(error));
+        assert_eq!(stones.count(7), 1);
+        assert_eq!(stones.distribution().blinks, 0);
+    }
+}
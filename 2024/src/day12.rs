@@ -1,99 +1,222 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use crate::utils::create_map;
-use std::collections::{HashMap, HashSet, VecDeque};
+use crate::error::AocError;
+use crate::utils::{Grid, Point, DIRS_4, DIRS_8};
+use std::collections::{HashSet, VecDeque};
+
+//which cells touch each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    //squares sharing an edge
+    Four,
+    //squares sharing an edge or a corner, the fences still follow the edges
+    Eight,
+    //hexagons in offset coordinates, odd rows shifted half a cell to the right
+    Hex,
+}
 
-const DIRS: [(i16, i16); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+//up left, up right, left, right, down left, down right
+const HEX_EVEN_ROW: [Point; 6] = [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)];
+const HEX_ODD_ROW: [Point; 6] = [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0), (1, 1)];
+
+impl Neighbourhood {
+    //cells a region grows into
+    fn connected(&self, pos: Point) -> impl Iterator<Item = Point> {
+        let dirs: &[Point] = match self {
+            Neighbourhood::Four => &DIRS_4,
+            Neighbourhood::Eight => &DIRS_8,
+            Neighbourhood::Hex => hex_dirs(pos),
+        };
+        dirs.iter().map(move |&(dr, dc)| (pos.0 + dr, pos.1 + dc))
+    }
 
-struct Region {
-    area: usize,
-    perimeter: usize,
-    sides: usize,
+    //cells on the other side of each edge of this cell
+    fn edges(&self, pos: Point) -> impl Iterator<Item = Point> {
+        let dirs: &[Point] = match self {
+            Neighbourhood::Four | Neighbourhood::Eight => &DIRS_4,
+            Neighbourhood::Hex => hex_dirs(pos),
+        };
+        dirs.iter().map(move |&(dr, dc)| (pos.0 + dr, pos.1 + dc))
+    }
+}
+
+fn hex_dirs((row, _): Point) -> &'static [Point] {
+    if row.rem_euclid(2) == 0 {
+        &HEX_EVEN_ROW
+    } else {
+        &HEX_ODD_ROW
+    }
+}
+
+//a garden plot: connected cells growing the same plant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub plant_type: char,
+    pub cells: HashSet<Point>,
+    pub area: usize,
+    pub perimeter: usize,
+    pub sides: usize,
+    //top left and bottom right corners, both included
+    pub bounds: (Point, Point),
+    pub neighbourhood: Neighbourhood,
 }
 
 impl Region {
-    fn new(plant_type: char, points: Vec<(i16, i16)>, map: &HashMap<(i16, i16), char>) -> Self {
-        let plant_type = plant_type;
+    fn new(plant_type: char, points: Vec<Point>, neighbourhood: Neighbourhood) -> Self {
         let area = points.len();
-        let perimeter = points.iter().map(|&(x, y)| {
-            let neighbors = vec![
-                (x - 1, y),
-                (x + 1, y),
-                (x, y - 1),
-                (x, y + 1),
-            ];
-
-            neighbors.iter().filter(|&&neighbor| {
-                match map.get(&neighbor) {
-                    Some(&neighbor_type) => neighbor_type != plant_type,
-                    None => true, 
-                }
-            }).count()
-        }).sum();
-        
-        let sides = count_corners(&points, plant_type, map);
+        let cells: HashSet<Point> = points.into_iter().collect();
+        let perimeter = cells
+            .iter()
+            .map(|&cell| neighbourhood.edges(cell).filter(|next| !cells.contains(next)).count())
+            .sum();
+
+        //no two hexagon edges line up, every edge of the fence is a side of its own
+        let sides = match neighbourhood {
+            Neighbourhood::Four | Neighbourhood::Eight => count_corners(&cells),
+            Neighbourhood::Hex => perimeter,
+        };
+        let rows = cells.iter().map(|&(row, _)| row);
+        let cols = cells.iter().map(|&(_, col)| col);
+        let bounds = (
+            (rows.clone().min().unwrap_or(0), cols.clone().min().unwrap_or(0)),
+            (rows.max().unwrap_or(0), cols.max().unwrap_or(0)),
+        );
 
         Self {
+            plant_type,
+            cells,
             area,
             perimeter,
             sides,
+            bounds,
+            neighbourhood,
         }
     }
 
-    fn price(&self) -> usize {
+    pub fn price(&self) -> usize {
         self.area * self.perimeter
     }
 
-    fn price_with_sides(&self) -> usize {
+    pub fn price_with_sides(&self) -> usize {
         self.area * self.sides
     }
-}
 
+    //cells the region closes in on every side, grouped by the pocket they form
+    pub fn holes(&self) -> Vec<HashSet<Point>> {
+        let ((top, left), (bottom, right)) = self.bounds;
+
+        //everything reachable from just outside the bounding box is open
+        let mut open = HashSet::new();
+        let mut queue: VecDeque<Point> = VecDeque::from([(top - 1, left - 1)]);
+        while let Some(cell) = queue.pop_front() {
+            for next in self.neighbourhood.edges(cell) {
+                let around = next.0 >= top - 1 && next.0 <= bottom + 1 && next.1 >= left - 1 && next.1 <= right + 1;
+                if around && !self.cells.contains(&next) && open.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut enclosed: HashSet<Point> = (top..=bottom)
+            .flat_map(|row| (left..=right).map(move |col| (row, col)))
+            .filter(|cell| !self.cells.contains(cell) && !open.contains(cell))
+            .collect();
+
+        let mut holes = Vec::new();
+        while let Some(&start) = enclosed.iter().next() {
+            let mut hole = HashSet::from([start]);
+            let mut queue = VecDeque::from([start]);
+            enclosed.remove(&start);
+            while let Some(cell) = queue.pop_front() {
+                for next in self.neighbourhood.edges(cell) {
+                    if enclosed.remove(&next) {
+                        hole.insert(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            holes.push(hole);
+        }
+        holes
+    }
+}
 
-#[aoc_generator(day12)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+//every region of the map, in reading order of their first cell
+pub fn regions(map: &Grid<char>) -> Vec<Region> {
+    regions_with(map, Neighbourhood::Four)
 }
 
-#[aoc(day12, part1)]
-pub fn solve_part1(input: &[String]) -> usize {
-    let map = create_map(input);
+pub fn regions_with(map: &Grid<char>, neighbourhood: Neighbourhood) -> Vec<Region> {
     let mut visited = HashSet::new();
-    let mut total_price = 0;
+    let mut regions = Vec::new();
 
-    for (&position, &plant_type) in &map {
+    for (position, &plant_type) in map.iter() {
         //if not visited
         if !visited.contains(&position) {
-            let points = bfs_region(position, plant_type, &map, &mut visited);
-            //create new region and calculate price
-            let region = Region::new(plant_type, points, &map);
-            total_price += region.price();
+            let points = bfs_region(position, plant_type, map, neighbourhood, &mut visited);
+            regions.push(Region::new(plant_type, points, neighbourhood));
         }
     }
 
-    total_price
+    regions
 }
 
-#[aoc(day12, part2)]
-pub fn solve_part2(input: &[String]) -> usize {
-    let map = create_map(input);
-    let mut visited = HashSet::new();
-    let mut total_price = 0;
+//indices of the regions lying in a hole of regions[outer]
+pub fn enclosed_regions(regions: &[Region], outer: usize) -> Vec<usize> {
+    let holes: HashSet<Point> = regions[outer].holes().into_iter().flatten().collect();
+    (0..regions.len())
+        .filter(|&index| index != outer && regions[index].cells.is_subset(&holes))
+        .collect()
+}
+
+//one line per region, most expensive fence first
+pub fn report(regions: &[Region]) -> String {
+    let mut order: Vec<usize> = (0..regions.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(regions[index].price()));
+
+    order
+        .into_iter()
+        .map(|index| {
+            let region = &regions[index];
+            let ((top, left), (bottom, right)) = region.bounds;
+            format!(
+                "{} #{}: area {}, perimeter {}, sides {}, price {}, bulk price {}, rows {}-{}, columns {}-{}\n",
+                region.plant_type,
+                index,
+                region.area,
+                region.perimeter,
+                region.sides,
+                region.price(),
+                region.price_with_sides(),
+                top,
+                bottom,
+                left,
+                right
+            )
+        })
+        .collect()
+}
 
-    for (&position, &plant_type) in &map {
-        if !visited.contains(&position) {
-            let points = bfs_region(position, plant_type, &map, &mut visited);
-            let region = Region::new(plant_type, points, &map);
-            total_price += region.price_with_sides();
-        }
-    }
 
-    total_price
+#[aoc_generator(day12)]
+pub fn generate_input(input: &str) -> Result<Grid<char>, AocError> {
+    Grid::parse(12, input)
+}
+
+#[aoc(day12, part1)]
+pub fn solve_part1(map: &Grid<char>) -> usize {
+    regions(map).iter().map(Region::price).sum()
+}
+
+#[aoc(day12, part2)]
+pub fn solve_part2(map: &Grid<char>) -> usize {
+    regions(map).iter().map(Region::price_with_sides).sum()
 }
 
 fn bfs_region(
     start: (i16, i16),
     plant_type: char,
-    map: &HashMap<(i16, i16), char>,
+    map: &Grid<char>,
+    neighbourhood: Neighbourhood,
     visited: &mut HashSet<(i16, i16)>,
 ) -> Vec<(i16, i16)> {
 
@@ -103,18 +226,12 @@ fn bfs_region(
     queue.push_back(start);
     visited.insert(start);
 
-    while let Some((x, y)) = queue.pop_front() {
-        points.push((x, y));
+    while let Some(current) = queue.pop_front() {
+        points.push(current);
 
-        for &(dx, dy) in &[(0, 1), (1, 0), (0, -1), (-1, 0)] {
-            let neighbor = (x + dx, y + dy);
-            if !visited.contains(&neighbor) {
-                if let Some(&neighbor_type) = map.get(&neighbor) {
-                    if neighbor_type == plant_type {
-                        visited.insert(neighbor);
-                        queue.push_back(neighbor);
-                    }
-                }
+        for neighbor in neighbourhood.connected(current) {
+            if map.get(neighbor) == Some(&plant_type) && visited.insert(neighbor) {
+                queue.push_back(neighbor);
             }
         }
     }
@@ -122,18 +239,13 @@ fn bfs_region(
     points
 }
 
-fn count_corners(
-    vec_region: &Vec<(i16, i16)>,
-    _plant_type: char,
-    _map: &HashMap<(i16, i16), char>,
-) -> usize {
-    let region: HashSet<(i16, i16)> = vec_region.iter().cloned().collect();
+fn count_corners(region: &HashSet<Point>) -> usize {
     let mut sides = 0;
 
-    for &missing_dir in DIRS.iter() {
+    for &missing_dir in DIRS_4.iter() {
         let mut found: HashSet<(i16, i16)> = HashSet::new();
 
-        for &cell in &region {
+        for &cell in region {
             if found.contains(&cell) {
                 continue;
             }
@@ -169,6 +281,68 @@ fn count_corners(
         }
     }
 
-    //println!("Plant Type: {}  Sides: {}", _plant_type, sides);
     sides
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCLOSED: &str = "OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO";
+
+    #[test]
+    fn test_day12_part1() {
+        let map = generate_input("AAAA\nBBCD\nBBCC\nEEEC").unwrap();
+        assert_eq!(solve_part1(&map), 140);
+        assert_eq!(solve_part1(&generate_input(ENCLOSED).unwrap()), 772);
+    }
+
+    #[test]
+    fn test_day12_part2() {
+        let map = generate_input("AAAA\nBBCD\nBBCC\nEEEC").unwrap();
+        assert_eq!(solve_part2(&map), 80);
+        assert_eq!(solve_part2(&generate_input(ENCLOSED).unwrap()), 436);
+    }
+
+    #[test]
+    fn test_day12_regions() {
+        let map = generate_input(ENCLOSED).unwrap();
+        let regions = regions(&map);
+        assert_eq!(regions.len(), 5);
+
+        let garden = &regions[0];
+        assert_eq!((garden.plant_type, garden.area, garden.bounds), ('O', 21, ((0, 0), (4, 4))));
+        assert_eq!(garden.holes().len(), 4);
+        assert!(garden.holes().contains(&HashSet::from([(3, 3)])));
+        assert_eq!(enclosed_regions(&regions, 0), vec![1, 2, 3, 4]);
+        assert!(regions[1].holes().is_empty());
+        assert!(enclosed_regions(&regions, 1).is_empty());
+
+        let report = report(&regions);
+        assert_eq!(report.lines().count(), 5);
+        assert!(report.starts_with("O #0: area 21, perimeter 36, sides 20, price 756, bulk price 420, rows 0-4, columns 0-4\n"));
+        assert!(report.ends_with("X #4: area 1, perimeter 4, sides 4, price 4, bulk price 4, rows 3-3, columns 3-3\n"));
+    }
+
+    #[test]
+    fn test_day12_neighbourhoods() {
+        let map = generate_input("AB\nBA").unwrap();
+        assert_eq!(regions_with(&map, Neighbourhood::Four).len(), 4);
+
+        //the diagonal cells join but each keeps its own fence
+        let eight = regions_with(&map, Neighbourhood::Eight);
+        assert_eq!(eight.len(), 2);
+        assert_eq!((eight[0].area, eight[0].perimeter, eight[0].sides), (2, 8, 8));
+
+        //in hexagons only the second row's left cell touches the first row's right cell
+        let hex = regions_with(&map, Neighbourhood::Hex);
+        assert_eq!(hex.iter().map(|region| region.area).collect::<Vec<_>>(), vec![1, 2, 1]);
+        assert_eq!((hex[1].perimeter, hex[1].sides), (10, 10));
+
+        let map = generate_input("AAA\nABA\nAAA").unwrap();
+        let hex = regions_with(&map, Neighbourhood::Hex);
+        assert_eq!((hex[1].plant_type, hex[1].perimeter), ('B', 6));
+        assert_eq!(enclosed_regions(&hex, 0), vec![1]);
+        assert_eq!(hex[0].perimeter, 8 * 6 - 2 * 10);
+    }
+}
@@ -1,8 +1,12 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
+use crate::error::{AocError, Line};
 
+const A_COST: i64 = 3;
+const B_COST: i64 = 1;
 
-struct ClawMachine {
+#[derive(Debug, Clone)]
+pub struct ClawMachine {
     button_a: (i64, i64),
     price_a: i64,
     button_b: (i64, i64),
@@ -21,12 +25,16 @@ impl ClawMachine {
         }
     }
 
+    //None when the prize can't be reached with whole presses
     pub fn calculate_price(&self) -> Option<i64> {
         let (ax, ay) = self.button_a;
         let (bx, by) = self.button_b;
         let (px, py) = self.prize;
 
         let determinant = (ax * by) - (ay * bx);
+        if determinant == 0 {
+            return self.collinear_price();
+        }
 
         let di = (px * by) - (py * bx);
         let dj = (py * ax) - (px * ay);
@@ -42,13 +50,91 @@ impl ClawMachine {
         Some((cost_a * self.price_a) + (cost_b * self.price_b))
        
     }
+
+    //both buttons move the claw along the same line, the prize has to lie on it
+    //and many ways of splitting the presses may reach it
+    fn collinear_price(&self) -> Option<i64> {
+        let (ax, ay) = self.button_a;
+        let (bx, by) = self.button_b;
+        let (px, py) = self.prize;
+
+        if self.button_a == (0, 0) && self.button_b == (0, 0) {
+            return (self.prize == (0, 0)).then_some(0);
+        }
+        if ax * py - ay * px != 0 || bx * py - by * px != 0 {
+            return None;
+        }
+
+        //a point of the line is known from one coordinate, unless the line runs along the other axis
+        let (a, b, p) = if ax != 0 || bx != 0 { (ax, bx, px) } else { (ay, by, py) };
+        let price = cheapest_presses(a as i128, self.price_a as i128, b as i128, self.price_b as i128, p as i128)?;
+        i64::try_from(price).ok()
+    }
+}
+
+//the cheapest i, j >= 0 with a * i + b * j = p, pressing a costs cost_a and b costs cost_b
+fn cheapest_presses(a: i128, cost_a: i128, b: i128, cost_b: i128, p: i128) -> Option<i128> {
+    let single = |step: i128, cost: i128| (step != 0 && p % step == 0 && p / step >= 0).then(|| p / step * cost);
+    if p == 0 {
+        return Some(0);
+    }
+    if a == 0 || b == 0 {
+        return if a == 0 { single(b, cost_b) } else { single(a, cost_a) };
+    }
+
+    let (g, x, y) = extended_gcd(a, b);
+    if p % g != 0 {
+        return None;
+    }
+
+    //every solution is (i0 + t * step_a, j0 - t * step_b), both counts bound t
+    let (i0, j0) = (x * (p / g), y * (p / g));
+    let (step_a, step_b) = (b / g, a / g);
+    let mut lowest: Option<i128> = None;
+    let mut highest: Option<i128> = None;
+    for (start, step) in [(i0, step_a), (j0, -step_b)] {
+        if step > 0 {
+            let bound = -floor_div(start, step);
+            lowest = Some(lowest.map_or(bound, |lowest| lowest.max(bound)));
+        } else {
+            let bound = floor_div(start, -step);
+            highest = Some(highest.map_or(bound, |highest| highest.min(bound)));
+        }
+    }
+    if let (Some(lowest), Some(highest)) = (lowest, highest) {
+        if lowest > highest {
+            return None;
+        }
+    }
+
+    //the price changes linearly with t, so the cheapest split is at one of the bounds
+    let slope = cost_a * step_a - cost_b * step_b;
+    let t = match slope.signum() {
+        1 => lowest?,
+        -1 => highest?,
+        _ => lowest.or(highest).unwrap_or(0),
+    };
+    Some(cost_a * (i0 + t * step_a) + cost_b * (j0 - t * step_b))
+}
+
+//g = gcd(a, b) > 0 with a * x + b * y = g
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return if a < 0 { (-a, -1, 0) } else { (a, 1, 0) };
+    }
+    let (g, x, y) = extended_gcd(b, a % b);
+    (g, y, x - (a / b) * y)
+}
+
+fn floor_div(n: i128, d: i128) -> i128 {
+    if d < 0 { (-n).div_euclid(-d) } else { n.div_euclid(d) }
 }
 
 
 
 #[aoc_generator(day13)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input
+pub fn generate_input(input: &str) -> Result<Vec<ClawMachine>, AocError> {
+    let chunks: Vec<String> = input
         .split("\n\n")
         .map(|chunk| {
             chunk
@@ -57,65 +143,70 @@ pub fn generate_input(input: &str) -> Vec<String> {
                 .collect::<Vec<_>>()
                 .join("\n") 
         })
-        .collect()
+        .collect();
+
+    parse_machines(&chunks, A_COST, B_COST)
 }
 
 #[aoc(day13, part1)]
-pub fn solve_part1(input: &[String]) -> i64 {
-    const A_COST: i64 = 3;
-    const B_COST: i64 = 1;
-
-    parse_machines(input, A_COST, B_COST)
-        .iter()
-        .filter_map(|machine| machine.calculate_price())
-        .sum()
+pub fn solve_part1(input: &[ClawMachine]) -> i64 {
+    total_price(input.iter().cloned())
 }
 
 #[aoc(day13, part2)]
-pub fn solve_part2(input: &[String]) -> i64 {
-    const A_COST: i64 = 3;
-    const B_COST: i64 = 1;
+pub fn solve_part2(input: &[ClawMachine]) -> i64 {
     const INCREMENT: i64 = 10000000000000;
 
-    parse_machines(input, A_COST, B_COST)
-        .iter()
-        .map(|machine| ClawMachine::new(
+    total_price(input.iter().map(|machine| ClawMachine::new(
             machine.button_a,
             machine.price_a,
             machine.button_b,
             machine.price_b,
             (machine.prize.0 + INCREMENT, machine.prize.1 + INCREMENT),
-        ))
-        .filter_map(|machine| machine.calculate_price())
-        .sum::<i64>()
+        )))
+}
+
+//machines whose prize can't be won cost nothing
+fn total_price(machines: impl Iterator<Item = ClawMachine>) -> i64 {
+    machines.filter_map(|machine| machine.calculate_price()).sum()
 }
 
-fn parse_machines(input: &[String], a_cost: i64, b_cost: i64) -> Vec<ClawMachine> {
+fn parse_machines(input: &[String], a_cost: i64, b_cost: i64) -> Result<Vec<ClawMachine>, AocError> {
     let re = Regex::new(
         r"Button A: X\+(\d+), Y\+(\d+)\nButton B: X\+(\d+), Y\+(\d+)\nPrize: X=(\d+), Y=(\d+)",
     )
     .unwrap();
 
+    //index of the first line of each chunk, chunks are separated by a blank line
+    let mut first_line = 0;
 
     input
         .iter()
-        .filter_map(|chunk| {
-            let captures = re.captures(chunk)?;
-            Some(ClawMachine::new(
-                (
-                    captures[1].parse().unwrap(),
-                    captures[2].parse().unwrap(),
-                ),
+        .map(|chunk| {
+            let chunk_start = first_line;
+            first_line += chunk.lines().count() + 1;
+
+            let captures = re.captures(chunk).ok_or(AocError::UnexpectedInput {
+                day: 13,
+                line: chunk_start + 1,
+                column: 1,
+                expected: "a claw machine with Button A, Button B and Prize lines",
+            })?;
+
+            //locate the capture inside the chunk to report its line
+            let number = |group: usize| -> Result<i64, AocError> {
+                let capture = captures.get(group).unwrap();
+                let line_index = chunk[..capture.start()].matches('\n').count();
+                let text = chunk.lines().nth(line_index).unwrap_or_default();
+                Line::new(13, chunk_start + line_index, text).parse(capture.as_str())
+            };
+
+            Ok(ClawMachine::new(
+                (number(1)?, number(2)?),
                 a_cost,
-                (
-                    captures[3].parse().unwrap(),
-                    captures[4].parse().unwrap(),
-                ),
-                b_cost, 
-                (
-                    captures[5].parse().unwrap(),
-                    captures[6].parse().unwrap(),
-                ),
+                (number(3)?, number(4)?),
+                b_cost,
+                (number(5)?, number(6)?),
             ))
         })
         .collect()
@@ -142,8 +233,25 @@ mod tests {
                           Button A: X+69, Y+23
                           Button B: X+27, Y+71
                           Prize: X=18641, Y=10279";
-            let input = generate_input(puzzle);
+            let input = generate_input(puzzle).unwrap();
             assert_eq!(solve_part1(&input), 480);
         }
+
+    #[test]
+    fn test_day13_collinear_buttons() {
+        let input = generate_input("Button A: X+2, Y+4\nButton B: X+1, Y+2\nPrize: X=10, Y=20").unwrap();
+        assert_eq!(input[0].calculate_price(), Some(10));
+        assert_eq!(solve_part1(&input), 10);
+
+        //pressing A is cheaper per step here, as long as B can make up the rest
+        let machine = |a: (i64, i64), b: (i64, i64), prize: (i64, i64)| ClawMachine::new(a, A_COST, b, B_COST, prize);
+        assert_eq!(machine((4, 4), (1, 1), (10, 10)).calculate_price(), Some(8));
+        assert_eq!(machine((4, 4), (1, 1), (10_000_000_000_001, 10_000_000_000_001)).calculate_price(), Some(7_500_000_000_001));
+        assert_eq!(machine((0, 3), (0, 2), (0, 7)).calculate_price(), Some(5));
+        assert_eq!(machine((2, 2), (4, 4), (5, 5)).calculate_price(), None);
+        assert_eq!(machine((2, 2), (4, 4), (6, 7)).calculate_price(), None);
+        assert_eq!(machine((0, 0), (0, 0), (0, 0)).calculate_price(), Some(0));
+        assert_eq!(machine((0, 0), (0, 0), (1, 0)).calculate_price(), None);
+    }
       
     }
\ No newline at end of file
@@ -2,9 +2,19 @@ use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
 use rayon::prelude::*;
 use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::Mutex;
+use crate::error::{AocError, Line};
 
-struct Robot{
+//size of the bathroom in the puzzle
+pub const WIDTH: i16 = 101;
+pub const HEIGHT: i16 = 103;
+
+#[derive(Debug, Clone)]
+pub struct Robot{
     position: (i16, i16),
     speed: (i16, i16),
 }
@@ -25,31 +35,52 @@ impl Robot{
             (y + dy).rem_euclid(height),
         );
     }
+
+    //where the robot is after t seconds, the walk repeats every width (height) seconds
+    pub fn position_at(&self, t: u64, width: i16, height: i16) -> (i16, i16) {
+        let along = |start: i16, speed: i16, size: i16| {
+            let size = i64::from(size);
+            let steps = (t % size as u64) as i64;
+            (i64::from(start) + i64::from(speed) * steps).rem_euclid(size) as i16
+        };
+        (
+            along(self.position.0, self.speed.0, width),
+            along(self.position.1, self.speed.1, height),
+        )
+    }
+}
+
+pub fn positions_at(robots: &[Robot], t: u64, width: i16, height: i16) -> Vec<(i16, i16)> {
+    robots.iter().map(|robot| robot.position_at(t, width, height)).collect()
+}
+
+pub fn quadrant_counts_at(robots: &[Robot], t: u64, width: i16, height: i16) -> [i32; 4] {
+    quadrant_counts(&positions_at(robots, t, width, height), width, height)
 }
 
 #[aoc_generator(day14)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Vec<Robot>, AocError> {
+    let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+    parse_robots(&lines)
 }
 
 #[aoc(day14, part1)]
-pub fn solve_part1(input: &[String]) -> i32 {
-    solve_part1_with_dimensions(input, 101, 103)
+pub fn solve_part1(input: &[Robot]) -> i32 {
+    solve_part1_with_dimensions(input, WIDTH, HEIGHT)
 }
 
-fn solve_part1_with_dimensions(input: &[String], width: i16, height: i16) -> i32 {
-    let mut robots = parse_robots(input);
+fn solve_part1_with_dimensions(input: &[Robot], width: i16, height: i16) -> i32 {
+    let quadrant_counts = quadrant_counts_at(input, 100, width, height);
+    println!("{:?}", quadrant_counts);
 
-    robots.iter_mut().for_each(|robot| {
-        for _ in 0..100 {
-            robot.move_position(width, height);
-        }
-    });
-    
-    let mut quadrant_counts = [0; 4];    
+    quadrant_counts.iter().product()
+}
+
+//robots in each quadrant, the ones on the middle row or column don't count
+fn quadrant_counts(positions: &[(i16, i16)], width: i16, height: i16) -> [i32; 4] {
+    let mut quadrant_counts = [0; 4];
 
-    robots.iter().for_each(|robot| {
-        let (x, y) = robot.position;
+    positions.iter().for_each(|&(x, y)| {
 
         if x == width / 2 || y == height / 2 {
             return;
@@ -66,25 +97,127 @@ fn solve_part1_with_dimensions(input: &[String], width: i16, height: i16) -> i32
         }
     });
 
-    println!("{:?}", quadrant_counts);
-
-    quadrant_counts.iter().product()    
+    quadrant_counts
 }
 
 #[aoc(day14, part2)]
-pub fn solve_part2(input: &[String]) -> i32 {
-    const WIDTH: i16 = 101;
-    const HEIGHT: i16 = 103;
-    const WIDTH_REQUIRED: usize = 20;
-    let debug = false;
+pub fn solve_part2(input: &[Robot]) -> Result<i32, AocError> {
+    find_tree_crt(input, WIDTH, HEIGHT).ok_or(AocError::Unsolvable {
+        day: 14,
+        reason: "the width and the height must not share a factor",
+    })
+}
+
+//how picture-like a frame looks, the lower the score the more likely the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detector {
+    //robots gathered close to their mean position
+    Variance,
+    //a picture leaves most quadrants nearly empty
+    SafetyFactor,
+    //robots concentrated in few blocks of the grid
+    Entropy,
+}
+
+//side of the blocks the entropy detector counts robots in
+const BLOCK: i16 = 4;
+
+impl Detector {
+    pub fn score(&self, positions: &[(i16, i16)], width: i16, height: i16) -> f64 {
+        match self {
+            Detector::Variance => {
+                variance(positions.iter().map(|position| position.0))
+                    + variance(positions.iter().map(|position| position.1))
+            }
+            Detector::SafetyFactor => quadrant_counts(positions, width, height).iter().product::<i32>() as f64,
+            Detector::Entropy => {
+                let mut blocks = HashMap::new();
+                for &(x, y) in positions {
+                    *blocks.entry((x / BLOCK, y / BLOCK)).or_insert(0) += 1;
+                }
+                let total = positions.len() as f64;
+                blocks
+                    .values()
+                    .map(|&count| {
+                        let p = count as f64 / total;
+                        -p * p.log2()
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+fn variance(values: impl Iterator<Item = i16> + Clone) -> f64 {
+    let count = values.clone().count().max(1) as f64;
+    let mean = values.clone().map(f64::from).sum::<f64>() / count;
+    values.map(|value| (f64::from(value) - mean).powi(2)).sum::<f64>() / count
+}
+
+//the picture repeats after width * height seconds, keep the best scoring second
+pub fn find_tree(input: &[Robot], width: i16, height: i16, detector: Detector) -> i32 {
+    let mut best = (f64::MAX, 0);
+
+    for second in 0..width as i32 * height as i32 {
+        let score = detector.score(&positions_at(input, second as u64, width, height), width, height);
+        if score < best.0 {
+            best = (score, second);
+        }
+    }
 
-    let mut robots = parse_robots(input);
+    best.1
+}
+
+//x repeats every width seconds and y every height seconds, so the second with the
+//tightest columns and the second with the tightest rows are combined with the CRT.
+//None when width and height are not coprime
+pub fn find_tree_crt(input: &[Robot], width: i16, height: i16) -> Option<i32> {
+    let x_offset = tightest_second(input, width, height, width, |position| position.0);
+    let y_offset = tightest_second(input, width, height, height, |position| position.1);
+    crt(x_offset, width as i32, y_offset, height as i32)
+}
+
+//second below period with the lowest variance of one coordinate
+fn tightest_second(
+    input: &[Robot],
+    width: i16,
+    height: i16,
+    period: i16,
+    axis: impl Fn(&(i16, i16)) -> i16,
+) -> i32 {
+    let mut best = (f64::MAX, 0);
+
+    for second in 0..period as i32 {
+        let score = variance(positions_at(input, second as u64, width, height).iter().map(&axis));
+        if score < best.0 {
+            best = (score, second);
+        }
+    }
+
+    best.1
+}
+
+//the t below a * b with t = x mod a and t = y mod b
+fn crt(x: i32, a: i32, y: i32, b: i32) -> Option<i32> {
+    //inverse of a modulo b, by brute force as b is small
+    let inverse = (1..b).find(|&k| (a * k).rem_euclid(b) == 1 % b).or((b == 1).then_some(0))?;
+    Some(x + a * ((y - x) * inverse).rem_euclid(b))
+}
+
+//the first second, up to ticks, where two rows hold a line of width_required robots.
+//every robot locks the cache and the matrix, kept as the baseline of the benchmark
+pub fn simulate_locked(input: &[Robot], width: i16, height: i16, ticks: u32, width_required: usize) -> Option<u32> {
+    let mut robots = input.to_vec();
     let mut moves = 0;
     let cache = Mutex::new(HashMap::new());
 
-    let matrix = Mutex::new(vec![vec![0; WIDTH as usize]; HEIGHT as usize]);
+    let matrix = Mutex::new(vec![vec![0; width as usize]; height as usize]);
+
+    while !is_christmas_tree(matrix.lock().unwrap().iter().map(|row| row.iter().copied()), width_required) {
+        if moves == ticks {
+            return None;
+        }
 
-    while !is_christmas_tree(&matrix.lock().unwrap(), WIDTH_REQUIRED) {
         //reset
         matrix.lock().unwrap().iter_mut().for_each(|row| row.iter_mut().for_each(|cell| *cell = 0));
 
@@ -97,11 +230,10 @@ pub fn solve_part2(input: &[String]) -> i32 {
                 let (x, y) = robot.position;
                 let (dx, dy) = robot.speed;
                 (
-                    (x + dx).rem_euclid(WIDTH),
-                    (y + dy).rem_euclid(HEIGHT),
+                    (x + dx).rem_euclid(width),
+                    (y + dy).rem_euclid(height),
                 )
             });
-            
 
             robot.position = new_position;
 
@@ -111,56 +243,164 @@ pub fn solve_part2(input: &[String]) -> i32 {
         });
 
         moves += 1;
-        if moves % 10000 == 0 {
-            println!("Moves: {}", moves);
+    }
+
+    Some(moves)
+}
+
+//robots per cell, counted from many threads at once
+pub struct Occupancy {
+    width: usize,
+    cells: Vec<AtomicU16>,
+}
+
+impl Occupancy {
+    pub fn new(width: i16, height: i16) -> Self {
+        Occupancy {
+            width: width as usize,
+            cells: (0..width as usize * height as usize).map(|_| AtomicU16::new(0)).collect(),
         }
     }
 
-    //print matrix
-    if debug {
-        for row in matrix.lock().unwrap().iter() {
-            for cell in row.iter() {
-                if *cell > 0 {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-            println!();
+    //no other thread can be counting while we hold it mutably
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell.get_mut() = 0);
+    }
+
+    pub fn add(&self, (x, y): (i16, i16)) {
+        self.cells[y as usize * self.width + x as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    //the counts read in place, row by row, once every robot has been added
+    pub fn counts(&self) -> impl Iterator<Item = impl Iterator<Item = i32> + '_> + '_ {
+        self.cells
+            .chunks(self.width)
+            .map(|row| row.iter().map(|cell| i32::from(cell.load(Ordering::Relaxed))))
+    }
+
+    pub fn rows(&mut self) -> Vec<Vec<i32>> {
+        self.cells
+            .chunks_mut(self.width)
+            .map(|row| row.iter_mut().map(|cell| i32::from(*cell.get_mut())).collect())
+            .collect()
+    }
+}
+
+//same search as simulate_locked, the robots move in parallel and only meet in
+//the atomic occupancy grid
+pub fn simulate_lock_free(input: &[Robot], width: i16, height: i16, ticks: u32, width_required: usize) -> Option<u32> {
+    let mut robots = input.to_vec();
+    let mut occupancy = Occupancy::new(width, height);
+
+    for second in 1..=ticks {
+        occupancy.clear();
+        robots.par_iter_mut().for_each(|robot| {
+            robot.move_position(width, height);
+            occupancy.add(robot.position);
+        });
+
+        if is_christmas_tree(occupancy.counts(), width_required) {
+            return Some(second);
+        }
+    }
+
+    None
+}
+
+//netpbm images, plain text so they open in any viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    //one bit per cell, black where a robot is
+    Pbm,
+    //grey levels counting the robots on each cell
+    Pgm,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Pbm => "pbm",
+            ImageFormat::Pgm => "pgm",
         }
     }
+}
 
-    moves
+//the picture of second t
+pub fn frame_image(robots: &[Robot], t: u64, width: i16, height: i16, format: ImageFormat) -> String {
+    let mut occupancy = Occupancy::new(width, height);
+    positions_at(robots, t, width, height).into_iter().for_each(|position| occupancy.add(position));
+    let rows = occupancy.rows();
+    //in grey, white for an empty cell and black for the most crowded one
+    let max = rows.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    let mut image = match format {
+        ImageFormat::Pbm => format!("P1\n{} {}\n", width, height),
+        ImageFormat::Pgm => format!("P2\n{} {}\n{}\n", width, height, max),
+    };
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|&count| match format {
+                ImageFormat::Pbm => i32::from(count > 0).to_string(),
+                ImageFormat::Pgm => (max - count).to_string(),
+            })
+            .collect();
+        image.push_str(&cells.join(" "));
+        image.push('\n');
+    }
+    image
 }
 
+//one numbered file per second, the sequence plays back as an animation
+pub fn export_frames(
+    robots: &[Robot],
+    seconds: impl IntoIterator<Item = u64>,
+    width: i16,
+    height: i16,
+    format: ImageFormat,
+    directory: &Path,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
+    seconds
+        .into_iter()
+        .map(|t| {
+            let path = directory.join(format!("day14_{:06}.{}", t, format.extension()));
+            fs::write(&path, frame_image(robots, t, width, height, format))?;
+            Ok(path)
+        })
+        .collect()
+}
 
-fn parse_robots(input: &[String]) -> Vec<Robot> {
+fn parse_robots(input: &[String]) -> Result<Vec<Robot>, AocError> {
     let re = Regex::new(r"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)").unwrap();
     input
         .iter()
-        .map(|line| {
-            let caps = re.captures(line).unwrap();
-            Robot::new(
-                (
-                    caps[1].parse().unwrap(),
-                    caps[2].parse().unwrap(),
-                ),
-                (
-                    caps[3].parse().unwrap(),
-                    caps[4].parse().unwrap(),
-                ),
-            )
+        .enumerate()
+        .map(|(index, text)| {
+            let line = Line::new(14, index, text);
+            let caps = re
+                .captures(text)
+                .ok_or(line.expected(text, "a robot like p=0,4 v=3,-3"))?;
+            let number = |group: usize| line.parse::<i16>(caps.get(group).unwrap().as_str());
+            Ok(Robot::new(
+                (number(1)?, number(2)?),
+                (number(3)?, number(4)?),
+            ))
         })
         .collect()
 }
 
-fn is_christmas_tree(matrix: &[Vec<i32>], width_required: usize) -> bool {
+//the rows are read once, cell by cell, so they can come straight from the atomic grid
+fn is_christmas_tree(matrix: impl Iterator<Item = impl IntoIterator<Item = i32>>, width_required: usize) -> bool {
     //we search for the line that forms over the tree
     let mut full_width_count = 0;
 
-    for row in matrix.iter().filter(|row| row.iter().sum::<i32>() >= width_required as i32) {
-        let continuous_count = row.windows(width_required).any(|window| {
-            window.iter().all(|&cell| cell > 0)
+    for row in matrix {
+        //the longest run of occupied cells in the row
+        let mut run = 0;
+        let continuous_count = row.into_iter().any(|cell| {
+            run = if cell > 0 { run + 1 } else { 0 };
+            run >= width_required
         });
 
         if continuous_count {
@@ -193,8 +433,92 @@ mod tests {
                              p=7,3 v=-1,2
                              p=2,4 v=2,-3
                              p=9,5 v=-3,-3";
-        let parsed_input = generate_input(example_input);
-        assert_eq!(solve_part1_with_dimensions(&parsed_input,11,7), 12); 
+        let parsed_input = generate_input(example_input).unwrap();
+        assert_eq!(solve_part1_with_dimensions(&parsed_input,11,7), 12);
+    }
+
+    //robots that all meet in a small square at second 30, scattered before
+    fn gathering_robots(width: i16, height: i16) -> Vec<Robot> {
+        let mut seed = 7i32;
+        let mut next = |modulo: i16| {
+            seed = (seed * 1103 + 12345).rem_euclid(1 << 16);
+            (seed % modulo as i32) as i16
+        };
+        (0..40)
+            .map(|_| {
+                let end = (10 + next(3), 12 + next(3));
+                let speed = (next(width) - width / 2, next(height) - height / 2);
+                let start = (
+                    (end.0 - 30 * speed.0).rem_euclid(width),
+                    (end.1 - 30 * speed.1).rem_euclid(height),
+                );
+                Robot::new(start, speed)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_day14_detectors() {
+        let (width, height) = (31, 37);
+        let robots = gathering_robots(width, height);
+        for detector in [Detector::Variance, Detector::SafetyFactor, Detector::Entropy] {
+            assert_eq!(find_tree(&robots, width, height, detector), 30, "{:?}", detector);
+        }
+        assert_eq!(find_tree_crt(&robots, width, height), Some(30));
+        assert_eq!(find_tree_crt(&robots, 30, 36), None);
+
+        assert_eq!(crt(2, 3, 3, 5), Some(8));
+        assert_eq!(crt(0, 1, 4, 7), Some(4));
+    }
+
+    #[test]
+    fn test_day14_simulations() {
+        //two rows of 8 robots lining up at second 12, next to the gathering ones
+        let (width, height) = (31, 37);
+        let mut robots = gathering_robots(width, height);
+        for (index, robot) in robots.clone().iter().take(16).enumerate() {
+            let end = (20 + index as i16 % 8, 2 + index as i16 / 8);
+            let speed = robot.speed;
+            let start = ((end.0 - 12 * speed.0).rem_euclid(width), (end.1 - 12 * speed.1).rem_euclid(height));
+            robots.push(Robot::new(start, speed));
+        }
+
+        assert_eq!(simulate_locked(&robots, width, height, 100, 8), Some(12));
+        assert_eq!(simulate_lock_free(&robots, width, height, 100, 8), Some(12));
+        assert_eq!(simulate_locked(&robots, width, height, 11, 8), None);
+        assert_eq!(simulate_lock_free(&robots, width, height, 11, 8), None);
+    }
+
+    #[test]
+    fn test_day14_images() {
+        let robots = vec![Robot::new((0, 0), (1, 0)), Robot::new((2, 1), (0, 0)), Robot::new((1, 1), (1, 0))];
+        assert_eq!(frame_image(&robots, 0, 3, 2, ImageFormat::Pbm), "P1\n3 2\n1 0 0\n0 1 1\n");
+        assert_eq!(frame_image(&robots, 1, 3, 2, ImageFormat::Pgm), "P2\n3 2\n2\n2 1 2\n2 2 0\n");
+
+        let directory = std::env::temp_dir().join(format!("day14_images_test_{}", std::process::id()));
+        let paths = export_frames(&robots, 0..3, 3, 2, ImageFormat::Pbm, &directory).unwrap();
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[2].file_name().unwrap(), "day14_000002.pbm");
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), frame_image(&robots, 0, 3, 2, ImageFormat::Pbm));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_day14_positions_at() {
+        let robot = Robot::new((2, 4), (2, -3));
+        let mut stepped = robot.clone();
+        for t in 0..=5 {
+            assert_eq!(robot.position_at(t, 11, 7), stepped.position);
+            stepped.move_position(11, 7);
+        }
+        assert_eq!(robot.position_at(5, 11, 7), (1, 3));
+
+        //a whole number of periods brings every robot back
+        let robots = gathering_robots(31, 37);
+        let period = 31 * 37;
+        assert_eq!(positions_at(&robots, u64::MAX - u64::MAX % period, 31, 37), positions_at(&robots, 0, 31, 37));
+        assert_eq!(positions_at(&robots, u64::MAX, 31, 37), positions_at(&robots, u64::MAX % period, 31, 37));
+        assert_eq!(quadrant_counts_at(&robots, 30, 31, 37), [40, 0, 0, 0]);
     }
 
 }
@@ -1,6 +1,8 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashMap;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use crate::error::{AocError, Line};
+use crate::utils::{Grid, Point};
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -24,15 +26,41 @@ impl Pos {
         (self.y as u32) * 100 + (self.x as u32)
     }
 
+    //grid position, rows first
+    pub fn point(&self) -> Point {
+        (self.y as i16, self.x as i16)
+    }
+
+}
+
+//what a single robot move did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveEvent {
+    Moved,
+    //a wall is in the way of the robot or of the boxes it pushes
+    Blocked,
+    //the robot moved and pushed this many boxes
+    Pushed(usize),
 }
 
-#[derive(Debug,Clone)]
+impl fmt::Display for MoveEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveEvent::Moved => write!(f, "moved"),
+            MoveEvent::Blocked => write!(f, "blocked by a wall"),
+            MoveEvent::Pushed(1) => write!(f, "pushed 1 box"),
+            MoveEvent::Pushed(boxes) => write!(f, "pushed {} boxes", boxes),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EntityType {
     Robot(Robot),
     StorageBox(Pos),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Robot {
     pub position: Pos,
 }
@@ -41,359 +69,367 @@ impl Robot {
     pub fn new(position: Pos) -> Self {
         Robot { position }
     }
+}
 
-    pub fn attempt_move(
-        &mut self,
-        direction: (i16, i16),
-        grid: &Vec<Vec<char>>,
-        entities: &mut HashMap<Pos, EntityType>,
-    ) -> bool {
-        let Some(new_robot_pos) = self.position.move_by(direction.0, direction.1) else {
-            //out of bounds
-            return false;
-        };
-    
-        //wall
-        if grid.get(new_robot_pos.y as usize)
-               .and_then(|row| row.get(new_robot_pos.x as usize))
-               == Some(&'#') 
-        {
-            return false;
+//size of a box in cells, a box is stored at its top left cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Footprint {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Footprint {
+    pub const SINGLE: Footprint = Footprint { width: 1, height: 1 };
+    pub const DOUBLE: Footprint = Footprint { width: 2, height: 1 };
+
+    pub fn cells(&self, origin: Pos) -> impl Iterator<Item = Pos> {
+        let Footprint { width, height } = *self;
+        (0..height).flat_map(move |dy| (0..width).map(move |dx| Pos::new(origin.x + dx, origin.y + dy)))
+    }
+}
+
+//walls and anything outside the map stop a move
+fn is_wall(grid: &Grid<char>, pos: Pos) -> bool {
+    grid.get(pos.point()).is_none_or(|&ch| ch == '#')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warehouse {
+    pub grid: Grid<char>,
+    pub entities: HashMap<Pos, EntityType>,
+    robot_pos: Pos,
+    footprint: Footprint,
+    //every cell covered by a box, pointing to the cell the box is stored at
+    occupied: HashMap<Pos, Pos>,
+}
+
+impl Warehouse {
+    pub fn new(grid: Grid<char>, entities: HashMap<Pos, EntityType>) -> Result<Self, AocError> {
+        Warehouse::with_footprint(grid, entities, Footprint::SINGLE)
+    }
+
+    //the entities must hold exactly one robot
+    pub fn with_footprint(
+        grid: Grid<char>,
+        entities: HashMap<Pos, EntityType>,
+        footprint: Footprint,
+    ) -> Result<Self, AocError> {
+        let mut robots = entities.iter().filter_map(|(pos, entity)| match entity {
+            EntityType::Robot(_) => Some(*pos),
+            _ => None,
+        });
+        let robot_pos = robots.next().ok_or(AocError::MissingInput { day: 15, expected: "the robot `@`" })?;
+        if robots.next().is_some() {
+            return Err(AocError::Unsolvable { day: 15, reason: "the warehouse holds more than one robot" });
         }
-    
-        if let Some(EntityType::StorageBox(_)) = entities.get(&new_robot_pos) {
-            //a box is in the way
-            let mut box_chain = Vec::new();
-            let mut current_pos = new_robot_pos;
-    
-            //chain push the boxe
-            loop {
-                box_chain.push(current_pos);
-    
-                
-                let Some(next_pos) = current_pos.move_by(direction.0, direction.1) else {
-                    return false;
-                };
-    
-                //if wall 
-                if grid.get(next_pos.y as usize)
-                       .and_then(|row| row.get(next_pos.x as usize))
-                       == Some(&'#')
-                {
-                    return false;
-                }
-    
-                //if box
-                if let Some(EntityType::StorageBox(_)) = entities.get(&next_pos) {
-                    current_pos = next_pos;
-                } else {
-                    for &from in box_chain.iter().rev() {
-                        let push_to = from.move_by(direction.0, direction.1).unwrap();
-                        entities.remove(&from);
-                        entities.insert(push_to, EntityType::StorageBox(push_to));
+        let occupied = entities
+            .iter()
+            .filter(|(_, entity)| matches!(entity, EntityType::StorageBox(_)))
+            .flat_map(|(&origin, _)| footprint.cells(origin).map(move |cell| (cell, origin)))
+            .collect();
+
+        Ok(Warehouse {
+            grid,
+            entities,
+            robot_pos,
+            footprint,
+            occupied,
+        })
+    }
+
+    pub fn robot(&self) -> Pos {
+        self.robot_pos
+    }
+
+    pub fn footprint(&self) -> Footprint {
+        self.footprint
+    }
+
+    //a map drawn with `O` boxes or with `[]` boxes, the robot and the boxes are
+    //kept apart from the walls. a map without boxes is read as a narrow one
+    pub fn parse(map: &str) -> Result<Warehouse, AocError> {
+        let mut grid = Grid::parse(15, map)?;
+        let mut entities = HashMap::new();
+        let mut footprint = None;
+
+        let drawn = grid.clone();
+        for ((y, x), &ch) in drawn.iter() {
+            let pos = Pos::new(x as u16, y as u16);
+            let text = map.lines().nth(y as usize).unwrap_or_default();
+            let line = Line::new(15, y as usize, text);
+            let boxed = match ch {
+                '#' | '.' | ']' => None,
+                '@' => {
+                    if entities.values().any(|entity| matches!(entity, EntityType::Robot(_))) {
+                        return Err(line.expected(line.from_char(x as usize), "a single robot `@`"));
                     }
-    
-                    self.position = new_robot_pos;
-                    return true;
+                    entities.insert(pos, EntityType::Robot(Robot::new(pos)));
+                    None
                 }
+                'O' => Some(Footprint::SINGLE),
+                '[' => Some(Footprint::DOUBLE),
+                _ => return Err(line.expected(line.from_char(x as usize), "one of `#.@O[]`")),
+            };
+
+            //every `]` closes the `[` right before it
+            let closes_box = drawn.get((y, x - 1)) == Some(&'[');
+            if (ch == '[') != (drawn.get((y, x + 1)) == Some(&']')) || (ch == ']' && !closes_box) {
+                return Err(line.expected(line.from_char(x as usize), "boxes drawn as `[]`"));
+            }
+            if let Some(size) = boxed {
+                if *footprint.get_or_insert(size) != size {
+                    return Err(line.expected(line.from_char(x as usize), "boxes of a single width"));
+                }
+                entities.insert(pos, EntityType::StorageBox(pos));
+            }
+            if ch != '#' {
+                grid[(y, x)] = '.';
             }
-        } else {
-            self.position = new_robot_pos;
-            return true;
         }
+
+        Warehouse::with_footprint(grid, entities, footprint.unwrap_or(Footprint::SINGLE))
     }
 
-    pub fn attempt_move_2(
-        &mut self,
-        direction: (i16, i16),
-        grid: &Vec<Vec<char>>,
-        entities: &mut HashMap<Pos, EntityType>,
-    ) -> bool {
+    //every box a robot stepping into `target` would push, None if any of them is blocked
+    fn boxes_to_push(&self, target: Pos, direction: (i16, i16)) -> Option<Vec<Pos>> {
+        let mut boxes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([target]);
+
+        while let Some(cell) = queue.pop_front() {
+            if is_wall(&self.grid, cell) {
+                return None;
+            }
+            let Some(&origin) = self.occupied.get(&cell) else {
+                continue;
+            };
+            if !seen.insert(origin) {
+                continue;
+            }
+
+            boxes.push(origin);
+            //the cells this box moves into, apart from the ones it already covers
+            for cell in self.footprint.cells(origin) {
+                let next = cell.move_by(direction.0, direction.1)?;
+                if self.occupied.get(&next) != Some(&origin) {
+                    queue.push_back(next);
+                }
+            }
+        }
 
-        //outside the grid
-        let Some(new_robot_pos) = self.position.move_by(direction.0, direction.1) else {
-            return false;
+        Some(boxes)
+    }
+
+    //move the robot once, the boxes only move if all of them can
+    pub fn step(&mut self, movement: (i16, i16)) -> MoveEvent {
+        let Some(target) = self.robot_pos.move_by(movement.0, movement.1) else {
+            return MoveEvent::Blocked;
+        };
+        let Some(boxes) = self.boxes_to_push(target, movement) else {
+            return MoveEvent::Blocked;
         };
-    
-        //wall
-        if grid
-            .get(new_robot_pos.y as usize)
-            .and_then(|row| row.get(new_robot_pos.x as usize))
-            == Some(&'#')
-        {
-            return false;
+
+        //lift every box before dropping them, they may overlap their old places
+        for origin in &boxes {
+            self.entities.remove(origin);
+            for cell in self.footprint.cells(*origin) {
+                self.occupied.remove(&cell);
+            }
+        }
+        for origin in &boxes {
+            let moved = origin.move_by(movement.0, movement.1).unwrap();
+            self.entities.insert(moved, EntityType::StorageBox(moved));
+            for cell in self.footprint.cells(moved) {
+                self.occupied.insert(cell, moved);
+            }
         }
 
-        //if the robot is pushing the box secondary position
-        let secondary_pos = Pos::new(new_robot_pos.x.saturating_sub(1), new_robot_pos.y);
-    
-        //box in the way
-        if let Some(EntityType::StorageBox(_primary_pos)) = entities.get(&new_robot_pos)
-            .or_else(|| entities.get(&secondary_pos)) {
-                
-            let mut box_chain = Vec::new();
-            let mut queue = VecDeque::new();
-
-            if let Some(EntityType::StorageBox(primary_pos)) = entities.get(&new_robot_pos).or_else(|| entities.get(&secondary_pos)){
-                queue.push_back(*primary_pos);
-            } else {
-                return false;
-            };
+        self.entities.remove(&self.robot_pos);
+        self.robot_pos = target;
+        self.entities.insert(target, EntityType::Robot(Robot::new(target)));
 
-            while let Some(current_primary) = queue.pop_front() {
-                let Some(next_pos) = current_primary.move_by(direction.0, direction.1) else {
-                    return false;
-                };
-            
-                let primary_target_free = grid
-                    .get(next_pos.y as usize)
-                    .and_then(|row| row.get(next_pos.x as usize))
-                    != Some(&'#');
-                let secondary_target_free = grid
-                    .get(next_pos.y as usize)
-                    .and_then(|row| row.get(next_pos.x as usize + 1))
-                    != Some(&'#');
-            
-                if !primary_target_free || !secondary_target_free {
-                    return false;
-                }
-            
-                box_chain.push(current_primary);
-            
-                if direction != (1, 0) {
-                    if let Some(EntityType::StorageBox(primary_pos)) = entities.get(&next_pos) {
-                        if !box_chain.contains(primary_pos) {
-                            queue.push_back(*primary_pos);
-                            box_chain.push(*primary_pos);
-                        }
-                    } else if let Some(EntityType::StorageBox(primary_pos)) = entities.get(&Pos::new(next_pos.x.saturating_sub(1), next_pos.y)) {
-                        if !box_chain.contains(primary_pos) {
-                            queue.push_back(*primary_pos);
-                            box_chain.push(*primary_pos);
-                        }
-                    }
-                }
-                if direction != (-1, 0) {
-                    if let Some(EntityType::StorageBox(primary_pos)) = entities.get(&Pos::new(next_pos.x + 1, next_pos.y)) {
-                        if !box_chain.contains(primary_pos) {
-                            queue.push_back(*primary_pos);
-                            box_chain.push(*primary_pos);
-                        }
-                    } else if let Some(EntityType::StorageBox(primary_pos)) = entities.get(&next_pos) {
-                        if !box_chain.contains(primary_pos) {
-                            queue.push_back(*primary_pos);
-                            box_chain.push(*primary_pos);
-                        }
+        if boxes.is_empty() {
+            MoveEvent::Moved
+        } else {
+            MoveEvent::Pushed(boxes.len())
+        }
+    }
+
+    pub fn simulate(&mut self, movements: &[(i16, i16)]) {
+        for &movement in movements {
+            self.step(movement);
+        }
+    }
+
+    pub fn gps_sum(&self) -> u32 {
+        self.entities
+            .values()
+            .filter_map(|entity| match entity {
+                EntityType::StorageBox(pos) => Some(pos.gps_coordinate()),
+                _ => None,
+            })
+            .sum()
+    }
+
+    //the map as the puzzle draws it, boxes wider than one cell as [], [=] and so on
+    pub fn render(&self) -> String {
+        let mut final_map = self.grid.clone();
+        for (p, e) in &self.entities {
+            match e {
+                EntityType::Robot(_) => final_map[p.point()] = '@',
+                EntityType::StorageBox(_) => {
+                    for cell in self.footprint.cells(*p) {
+                        final_map[cell.point()] = match (self.footprint.width, cell.x - p.x) {
+                            (1, _) => 'O',
+                            (_, 0) => '[',
+                            (width, dx) if dx == width - 1 => ']',
+                            _ => '=',
+                        };
                     }
                 }
             }
-    
-            for &from in box_chain.iter().rev() {
-                let new_box_pos = from.move_by(direction.0, direction.1).unwrap();
-                entities.remove(&from);
-                entities.insert(new_box_pos, EntityType::StorageBox(new_box_pos));
-            }
-    
-            self.position = new_robot_pos;
-            return true;
         }
-    
-        self.position = new_robot_pos;
-        true
+        final_map.to_string()
     }
 }
 
-#[derive(Debug)]
-pub struct Warehouse {
-    pub grid: Vec<Vec<char>>,
-    pub entities: HashMap<Pos, EntityType>,
-    robot_pos: Pos,
+//one frame of a recorded simulation, the first one has no move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub movement: Option<(i16, i16)>,
+    pub event: Option<MoveEvent>,
+    pub map: String,
 }
 
-impl Warehouse {
-    pub fn new(grid: Vec<Vec<char>>, entities: HashMap<Pos, EntityType>) -> Self {
-        let robot_pos = entities
-            .iter()
-            .find_map(|(pos, entity)| match entity {
-                EntityType::Robot(_) => Some(*pos),
-                _ => None,
-            })
-            .unwrap();
-        Warehouse {
-            grid,
-            entities,
-            robot_pos,
+//keeps every frame of a simulation so runs can be replayed and compared
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recorder {
+    pub frames: Vec<Frame>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder::default()
+    }
+
+    //simulate the moves, recording the starting map and each move
+    pub fn record(&mut self, warehouse: &mut Warehouse, movements: &[(i16, i16)]) {
+        if self.frames.is_empty() {
+            self.frames.push(Frame { movement: None, event: None, map: warehouse.render() });
+        }
+        for &movement in movements {
+            let event = warehouse.step(movement);
+            self.frames.push(Frame { movement: Some(movement), event: Some(event), map: warehouse.render() });
         }
     }
 
-    pub fn simulate(&mut self, movements: Vec<(i16, i16)>) {
-        for movement in movements {
-            if let Some(EntityType::Robot(mut robot)) = self.entities.remove(&self.robot_pos) {
-                let movement_result = robot.attempt_move(movement, &self.grid, &mut self.entities);
-                if movement_result {
-                    self.robot_pos = robot.position;
+    //index of the first frame where the two recordings disagree
+    pub fn first_difference(&self, other: &Recorder) -> Option<usize> {
+        (0..self.frames.len().max(other.frames.len()))
+            .find(|&index| self.frames.get(index) != other.frames.get(index))
+    }
+
+    //every frame as text, headed by its move and what happened
+    pub fn dump(&self) -> String {
+        let mut text = String::new();
+        for (index, frame) in self.frames.iter().enumerate() {
+            match (frame.movement, frame.event) {
+                (Some(movement), Some(event)) => {
+                    text += &format!("move {} {}: {}\n", index, movement_symbol(movement), event);
                 }
-    
-                self.entities.insert(self.robot_pos, EntityType::Robot(robot));
+                _ => text += "initial state\n",
             }
+            text += &frame.map;
+            text += "\n";
         }
+        text
     }
+}
 
-    pub fn simulate_2(&mut self, movements: Vec<(i16, i16)>) {
-        for m in movements {
-            if let Some(EntityType::Robot(mut r)) = self.entities.remove(&self.robot_pos) {
-                let ok = r.attempt_move_2(m, &self.grid, &mut self.entities);
-                if ok { self.robot_pos = r.position; }
-                self.entities.insert(self.robot_pos, EntityType::Robot(r));
-            }
-            //print_warehouse_2(&self);
-        }
+fn movement_symbol(movement: (i16, i16)) -> char {
+    match movement {
+        (-1, 0) => '<',
+        (1, 0) => '>',
+        (0, -1) => '^',
+        _ => 'v',
     }
 }
 
 
 #[aoc_generator(day15)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.split("\n\n").map(|s| s.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<(Warehouse, Vec<(i16, i16)>), AocError> {
+    let parts: Vec<String> = input.split("\n\n").map(|s| s.to_string()).collect();
+    parse_warehouse(&parts)
 }
 
 #[aoc(day15, part1)]
-pub fn solve_part1(input: &[String]) -> u32 {
-    let (mut warehouse, movements) = parse_warehouse(input);
+pub fn solve_part1(input: &(Warehouse, Vec<(i16, i16)>)) -> u32 {
+    let (warehouse, movements) = input;
+    let mut warehouse = warehouse.clone();
     warehouse.simulate(movements);
     // Print warehouse
-    for (y, row) in warehouse.grid.iter().enumerate() {
-        for (x, &ch) in row.iter().enumerate() {
-            let pos = Pos::new(x as u16, y as u16);
-            if let Some(entity) = warehouse.entities.get(&pos) {
-                match entity {
-                    EntityType::Robot(_) => print!("@"),
-                    EntityType::StorageBox(_) => print!("O"),
-                }
-            } else {
-                print!("{}", ch);
-            }
-        }
-        println!();
-    }
-    warehouse
-        .entities
-        .values()
-        .filter_map(|entity| match entity {
-            EntityType::StorageBox(pos) => {
-                Some(pos.gps_coordinate())
-            }
-            _ => None,
-        })
-        .sum()
+    print!("{}", warehouse.render());
+    warehouse.gps_sum()
 }
 
 #[aoc(day15, part2)]
-pub fn solve_part2(input:&[String]) -> u32 {
-    let (mut w, moves) = parse_warehouse_part2(input);
-    print_warehouse_2(&w);
-    w.simulate_2(moves);
-    print_warehouse_2(&w);
-    w.entities.values().filter_map(|e| {
-        match e {
-            EntityType::StorageBox(pos) => {
-                let x = pos.x;
-                let y = pos.y;
-                Some(y as u32 * 100 + x as u32)
-            }
-            _ => None,
-        }
-    }).sum()   
-}
-pub fn print_warehouse_2(warehouse: &Warehouse) {
-    let mut final_map = warehouse.grid.clone();
-    for (p, e) in &warehouse.entities {
-        match e {
-            EntityType::Robot(_) => {
-                if p.x as usize + 1 < final_map[p.y as usize].len() {
-                    final_map[p.y as usize][p.x as usize] = '@';
-                } else {
-                    final_map[p.y as usize][p.x as usize] = '@';
-                }
-            }
-            EntityType::StorageBox(_) => {
-                if p.x as usize + 1 < final_map[p.y as usize].len() {
-                    final_map[p.y as usize][p.x as usize] = '[';
-                    final_map[p.y as usize][p.x as usize + 1] = ']';
-                } else {
-                    final_map[p.y as usize][p.x as usize] = 'X';
-                }
-            }
-        }
-    }
-    for row in final_map {
-        println!("{}", row.iter().collect::<String>());
-    }
+pub fn solve_part2(input: &(Warehouse, Vec<(i16, i16)>)) -> u32 {
+    let (original_w, moves) = input;
+    let mut w = widen_warehouse(original_w);
+    print!("{}", w.render());
+    w.simulate(moves);
+    print!("{}", w.render());
+    w.gps_sum()
 }
 
-pub fn parse_warehouse(parts: &[String]) -> (Warehouse, Vec<(i16, i16)>) {
-    let mut grid = Vec::new();
-    let mut entities = HashMap::new();
+pub fn parse_warehouse(parts: &[String]) -> Result<(Warehouse, Vec<(i16, i16)>), AocError> {
     let mut movements = Vec::new();
     //split the map from the movements
-    let map_lines = parts[0].lines();
-    
-    let directions = parts[1].lines().collect::<String>();//all movements in a single string
-
-    //parse the map
-    for (y, line) in map_lines.enumerate() {
-        let mut row = Vec::new();
-        for (x, ch) in line.chars().enumerate() {
-            match ch {
-                '#' => row.push('#'),
-                '.' => row.push('.'),
-                '@' => {
-                    row.push('.');
-                    let robot_pos = Pos::new(x as u16, y as u16);
-                    entities.insert(robot_pos, EntityType::Robot(Robot::new(robot_pos)));
-                }
-                'O' => {
-                    row.push('.');
-                    entities.insert(Pos::new(x as u16, y as u16), EntityType::StorageBox(Pos::new(x as u16, y as u16)));
-                }
-                _ => (),
-            }
-        }
-        grid.push(row);
+    let map = parts.first().ok_or(AocError::MissingInput { day: 15, expected: "the warehouse map" })?;
+    let directions = parts.get(1).ok_or(AocError::MissingInput { day: 15, expected: "the robot movements" })?;
+
+    let warehouse = Warehouse::parse(map)?;
+    //part 2 widens the map itself, so the puzzle input must be drawn narrow
+    if warehouse.footprint() != Footprint::SINGLE {
+        let (index, text) = map.lines().enumerate().find(|(_, text)| text.contains('[')).unwrap_or_default();
+        let line = Line::new(15, index, text);
+        return Err(line.expected(&text[text.find('[').unwrap_or_default()..], "boxes drawn as `O`"));
     }
 
-
-    //parse the movements 
-    for ch in directions.chars() {
-        let movement = match ch {
-            '<' => (-1, 0),
-            '>' => (1, 0),
-            '^' => (0, -1),
-            'v' => (0, 1),
-            _   => (0,0),
-        };
-        movements.push(movement);
+    //parse the movements, numbered after the map and the blank line
+    let offset = map.lines().count() + 1;
+    for (index, text) in directions.lines().enumerate() {
+        for (column, ch) in text.char_indices() {
+            let movement = match ch {
+                '<' => (-1, 0),
+                '>' => (1, 0),
+                '^' => (0, -1),
+                'v' => (0, 1),
+                _ => {
+                    let line = Line::new(15, offset + index, text);
+                    return Err(line.expected(&text[column..], "one of `<>^v`"));
+                }
+            };
+            movements.push(movement);
+        }
     }
 
-    (Warehouse::new(grid, entities), movements)
+    Ok((warehouse, movements))
 }
 
-pub fn parse_warehouse_part2(parts: &[String]) -> (Warehouse, Vec<(i16, i16)>) {
-    let (original_w, moves) = parse_warehouse(parts);
-    let mut new_grid = Vec::new();
+//a warehouse that is already wide is returned as it is
+pub fn widen_warehouse(original_w: &Warehouse) -> Warehouse {
+    if original_w.footprint() != Footprint::SINGLE {
+        return original_w.clone();
+    }
+    let mut new_grid = Grid::new(original_w.grid.width() * 2, original_w.grid.height(), '.');
     let mut new_entities = HashMap::new();
-    for row in original_w.grid {
-        let mut expanded_row = Vec::new();
-        for c in row {
-            if c == '#' { expanded_row.push('#'); expanded_row.push('#'); }
-            else if c == '.' { expanded_row.push('.'); expanded_row.push('.'); }
-            else if c == '@' { expanded_row.push('@'); expanded_row.push('.'); }
-            else if c == 'O' { expanded_row.push('['); expanded_row.push(']'); }
-            else { expanded_row.push(c); }
-        }
-        new_grid.push(expanded_row);
+    //the grid only holds walls and floor, the robot and the boxes are entities
+    for ((row, col), &c) in original_w.grid.iter() {
+        new_grid[(row, col * 2)] = c;
+        new_grid[(row, col * 2 + 1)] = c;
     }
-    for (p, e) in original_w.entities {
+    for (&p, e) in &original_w.entities {
         match e {
             EntityType::Robot(_) => {
                 let nx = p.x * 2;
@@ -407,7 +443,7 @@ pub fn parse_warehouse_part2(parts: &[String]) -> (Warehouse, Vec<(i16, i16)>) {
             }
         }
     }
-    (Warehouse::new(new_grid, new_entities), moves)
+    Warehouse::with_footprint(new_grid, new_entities, Footprint::DOUBLE).expect("the robot of the narrow warehouse")
 }
 
 #[cfg(test)]
@@ -437,7 +473,7 @@ mod tests {
                             "<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>\n",
                             "^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>\n",
                             "v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^");
-        let parsed_input = generate_input(example_input);
+        let parsed_input = generate_input(example_input).unwrap();
         let res = solve_part1(&parsed_input);
         println!("Result: {}", res);
         assert_eq!(res, 10092);
@@ -466,8 +502,8 @@ mod tests {
                             "<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>\n",
                             "^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>\n",
                             "v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^");
-        let parsed_input = generate_input(example_input);
-        assert_eq!(solve_part2(&parsed_input), 9021); 
+        let parsed_input = generate_input(example_input).unwrap();
+        assert_eq!(solve_part2(&parsed_input), 9021);
     }
     #[test]
     fn test_day15_minimal() {
@@ -482,7 +518,128 @@ mod tests {
             "\n",
             "<vv<<^^<<^^"
         );
-        let parsed_input = generate_input(example_input);
-        assert_eq!(solve_part2(&parsed_input), 105);
+        let parsed_input = generate_input(example_input).unwrap();
+        assert_eq!(solve_part2(&parsed_input), 618);
+    }
+
+    #[test]
+    fn test_day15_recorder() {
+        let example_input = concat!(
+            "#######\n",
+            "#...#.#\n",
+            "#.....#\n",
+            "#..OO@#\n",
+            "#..O..#\n",
+            "#.....#\n",
+            "#######\n",
+            "\n",
+            "<vv<<^^<<^^"
+        );
+        let (warehouse, movements) = generate_input(example_input).unwrap();
+        let mut warehouse = widen_warehouse(&warehouse);
+        let mut recorder = Recorder::new();
+        recorder.record(&mut warehouse, &movements);
+
+        let events: Vec<MoveEvent> = recorder.frames.iter().filter_map(|frame| frame.event).collect();
+        assert_eq!(events[0], MoveEvent::Pushed(2));
+        assert_eq!(events[1], MoveEvent::Moved);
+        assert_eq!(events[5], MoveEvent::Pushed(3));
+        assert_eq!(events[6], MoveEvent::Blocked);
+        assert_eq!(events.len(), movements.len());
+        assert_eq!(
+            recorder.frames[1].map,
+            "##############\n##......##..##\n##..........##\n##...[][]@..##\n##....[]....##\n##..........##\n##############\n"
+        );
+        assert!(recorder.dump().starts_with("initial state\n##############\n"));
+        assert!(recorder.dump().contains("move 1 <: pushed 2 boxes\n"));
+
+        //replaying the same moves gives the same frames
+        let (warehouse, _) = generate_input(example_input).unwrap();
+        let mut replay = Recorder::new();
+        replay.record(&mut widen_warehouse(&warehouse), &movements[..5]);
+        assert_eq!(recorder.first_difference(&replay), Some(6));
+    }
+
+    #[test]
+    fn test_day15_large_boxes() {
+        let grid = Grid::new(7, 7, '.');
+        let mut entities = HashMap::new();
+        //two 2x2 boxes, the upper one only half over the lower one
+        for pos in [Pos::new(2, 1), Pos::new(3, 3)] {
+            entities.insert(pos, EntityType::StorageBox(pos));
+        }
+        let robot = Pos::new(3, 6);
+        entities.insert(robot, EntityType::Robot(Robot::new(robot)));
+        let mut warehouse = Warehouse::with_footprint(grid, entities, Footprint { width: 2, height: 2 }).unwrap();
+        assert_eq!(warehouse.render(), ".......\n..[]...\n..[]...\n...[]..\n...[]..\n.......\n...@...\n");
+
+        //pushing up moves both boxes, the top one hits the edge of the map after one step
+        assert_eq!(warehouse.step((0, -1)), MoveEvent::Moved);
+        assert_eq!(warehouse.step((0, -1)), MoveEvent::Pushed(2));
+        assert_eq!(warehouse.step((0, -1)), MoveEvent::Blocked);
+        assert_eq!(warehouse.robot(), Pos::new(3, 4));
+        assert_eq!(warehouse.render(), "..[]...\n..[]...\n...[]..\n...[]..\n...@...\n.......\n.......\n");
+        assert_eq!(warehouse.gps_sum(), 2 + 203);
+    }
+
+    #[test]
+    fn test_day15_round_trip() {
+        let map = concat!(
+            "#######\n",
+            "#...#.#\n",
+            "#.....#\n",
+            "#..OO@#\n",
+            "#..O..#\n",
+            "#.....#\n",
+            "#######\n"
+        );
+        let movements = [(-1, 0), (0, 1), (0, 1), (-1, 0), (-1, 0), (0, -1)];
+        let warehouse = Warehouse::parse(map).unwrap();
+        assert_eq!(warehouse.render(), map);
+
+        //the wide map as drawn in the puzzle
+        let mut wide = widen_warehouse(&warehouse);
+        assert_eq!(
+            wide.render(),
+            "##############\n##......##..##\n##..........##\n##....[][]@.##\n##....[]....##\n##..........##\n##############\n"
+        );
+        assert_eq!(Warehouse::parse(&wide.render()), Ok(wide.clone()));
+        assert_eq!(widen_warehouse(&wide), wide);
+        assert_eq!(
+            generate_input(&format!("{}\n<v", wide.render())).err(),
+            Some(AocError::UnexpectedInput { day: 15, line: 4, column: 7, expected: "boxes drawn as `O`" })
+        );
+
+        //snapshots taken during a simulation read back to the same state
+        let mut narrow = warehouse.clone();
+        narrow.simulate(&movements);
+        wide.simulate(&movements);
+        for snapshot in [narrow, wide] {
+            let parsed = Warehouse::parse(&snapshot.render()).unwrap();
+            assert_eq!(parsed.render(), snapshot.render());
+            assert_eq!(parsed, snapshot);
+        }
+
+        assert_eq!(
+            Warehouse::parse("#[.@#"),
+            Err(AocError::UnexpectedInput { day: 15, line: 1, column: 2, expected: "boxes drawn as `[]`" })
+        );
+        assert_eq!(
+            Warehouse::parse("#[]O@#"),
+            Err(AocError::UnexpectedInput { day: 15, line: 1, column: 4, expected: "boxes of a single width" })
+        );
+        assert_eq!(
+            Warehouse::parse("#@.@#"),
+            Err(AocError::UnexpectedInput { day: 15, line: 1, column: 4, expected: "a single robot `@`" })
+        );
+        assert_eq!(
+            Warehouse::parse("#é.@x#"),
+            Err(AocError::UnexpectedInput { day: 15, line: 1, column: 2, expected: "one of `#.@O[]`" })
+        );
+        assert_eq!(Warehouse::parse("#..#"), Err(AocError::MissingInput { day: 15, expected: "the robot `@`" }));
+        assert_eq!(
+            Warehouse::new(Grid::new(2, 1, '.'), HashMap::new()),
+            Err(AocError::MissingInput { day: 15, expected: "the robot `@`" })
+        );
     }
 }
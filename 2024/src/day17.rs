@@ -1,38 +1,307 @@
 use aoc_runner_derive::{aoc, aoc_generator};
+use crate::error::{AocError, Line};
+
+//initial registers and program of the device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    pub a_reg: u64,
+    pub b_reg: u64,
+    pub c_reg: u64,
+    pub program: Vec<u8>,
+}
 
 #[aoc_generator(day17)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Device, AocError> {
+    let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+    let (a_reg, b_reg, c_reg, program) = parse_input(&lines)?;
+    Ok(Device { a_reg, b_reg, c_reg, program })
+}
+
+//the eight instructions, their value is the opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Adv = 0,
+    Bxl = 1,
+    Bst = 2,
+    Jnz = 3,
+    Bxc = 4,
+    Out = 5,
+    Bdv = 6,
+    Cdv = 7,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 8] = [
+        Opcode::Adv,
+        Opcode::Bxl,
+        Opcode::Bst,
+        Opcode::Jnz,
+        Opcode::Bxc,
+        Opcode::Out,
+        Opcode::Bdv,
+        Opcode::Cdv,
+    ];
+
+    pub fn from_value(value: u8) -> Option<Opcode> {
+        Opcode::ALL.get(value as usize).copied()
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        Opcode::ALL.into_iter().find(|opcode| opcode.mnemonic() == mnemonic)
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Adv => "adv",
+            Opcode::Bxl => "bxl",
+            Opcode::Bst => "bst",
+            Opcode::Jnz => "jnz",
+            Opcode::Bxc => "bxc",
+            Opcode::Out => "out",
+            Opcode::Bdv => "bdv",
+            Opcode::Cdv => "cdv",
+        }
+    }
+
+    //combo operands name a register from 4 to 6, the others are literals
+    pub fn takes_combo(self) -> bool {
+        matches!(self, Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv)
+    }
+}
+
+//registers of the 3-bit computer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+}
+
+//one executed instruction and the registers right after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub pointer: usize,
+    pub opcode: u8,
+    pub operand: u8,
+    pub registers: Registers,
+}
+
+//a program long enough to need more instructions is assumed to loop forever
+pub const INSTRUCTION_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone)]
+pub struct Computer<'a> {
+    pub registers: Registers,
+    pub pointer: usize,
+    pub output: Vec<u8>,
+    program: &'a [u8],
+    executed: usize,
+    limit: usize,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl<'a> Computer<'a> {
+    pub fn new(program: &'a [u8], registers: Registers) -> Self {
+        Computer {
+            registers,
+            pointer: 0,
+            output: Vec::new(),
+            program,
+            executed: 0,
+            limit: INSTRUCTION_LIMIT,
+            trace: None,
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    //record every executed instruction, see trace()
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    pub fn executed(&self) -> usize {
+        self.executed
+    }
+
+    //the machine halts when the pointer leaves the program
+    pub fn halted(&self) -> bool {
+        self.pointer + 1 >= self.program.len()
+    }
+
+    fn combo(&self, operand: u8) -> Result<u64, AocError> {
+        match operand {
+            0..=3 => Ok(operand as u64),
+            4 => Ok(self.registers.a),
+            5 => Ok(self.registers.b),
+            6 => Ok(self.registers.c),
+            _ => Err(AocError::InvalidOperand { day: 17, pointer: self.pointer, operand: operand as u64 }),
+        }
+    }
+
+    //execute a single instruction, false once the program has halted
+    pub fn step(&mut self) -> Result<bool, AocError> {
+        if self.halted() {
+            return Ok(false);
+        }
+        if self.executed >= self.limit {
+            return Err(AocError::InstructionLimit { day: 17, limit: self.limit });
+        }
+
+        let pointer = self.pointer;
+        let (opcode, operand) = (self.program[pointer], self.program[pointer + 1]);
+        let mut next = pointer + 2;
+
+        //parse_input only lets 3-bit values through, but any program can be run
+        let Some(instruction) = Opcode::from_value(opcode) else {
+            return Err(AocError::InvalidOpcode { day: 17, pointer, opcode });
+        };
+
+        match instruction {
+            //adv: divide A by 2^combo
+            Opcode::Adv => self.registers.a = shift(self.registers.a, self.combo(operand)?),
+            //bxl: XOR B with the literal operand
+            Opcode::Bxl => self.registers.b ^= operand as u64,
+            //bst: combo modulo 8 into B
+            Opcode::Bst => self.registers.b = self.combo(operand)? & 7,
+            //jnz: jump to the literal operand unless A is 0
+            Opcode::Jnz => {
+                if self.registers.a != 0 {
+                    next = operand as usize;
+                }
+            }
+            //bxc: XOR B with C, the operand is ignored
+            Opcode::Bxc => self.registers.b ^= self.registers.c,
+            //out: output combo modulo 8
+            Opcode::Out => {
+                let value = self.combo(operand)? & 7;
+                self.output.push(value as u8);
+            }
+            //bdv: A divided by 2^combo into B
+            Opcode::Bdv => self.registers.b = shift(self.registers.a, self.combo(operand)?),
+            //cdv: A divided by 2^combo into C
+            Opcode::Cdv => self.registers.c = shift(self.registers.a, self.combo(operand)?),
+        }
+
+        self.pointer = next;
+        self.executed += 1;
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry { pointer, opcode, operand, registers: self.registers });
+        }
+
+        Ok(true)
+    }
+
+    //run until the program halts and return everything it printed
+    pub fn run(&mut self) -> Result<&[u8], AocError> {
+        while self.step()? {}
+        Ok(&self.output)
+    }
+}
+
+//division by a power of two, anything past 63 bits leaves nothing
+fn shift(value: u64, amount: u64) -> u64 {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shr(amount))
+        .unwrap_or(0)
+}
+
+impl Device {
+    pub fn computer(&self) -> Computer<'_> {
+        Computer::new(&self.program, Registers { a: self.a_reg, b: self.b_reg, c: self.c_reg })
+    }
 }
 
 #[aoc(day17, part1)]
-pub fn solve_part1(input: &[String]) -> String {
-    let (a_reg, b_reg, c_reg, program) = parse_input(input);
-    let (instruction, operand) = parse_instruction(&program, 0); //parse the first instruction and operand
-    let mut output_vec = Vec::with_capacity(program.len());
-    compute(instruction, operand, &program, a_reg, b_reg, c_reg, 0, &mut output_vec)
+pub fn solve_part1(device: &Device) -> Result<String, AocError> {
+    let mut computer = device.computer();
+    Ok(computer
+        .run()?
         .iter()
         .map(|x| x.to_string())
         .collect::<Vec<_>>()
-        .join(",") //join the output vec into a string
+        .join(",")) //join the output vec into a string
 }
 
 #[aoc(day17, part2)]
-pub fn solve_part2(input: &[String]) -> u64 {
-    
-    let (_a_reg, _b_reg, _c_reg, program) = parse_input(input);
+pub fn solve_part2(device: &Device) -> Result<u64, AocError> {
+    reverse_engineer(device)
+}
 
-    reverse_engineer(&program).unwrap_or(0) 
+fn unsolvable(reason: &'static str) -> AocError {
+    AocError::Unsolvable { day: 17, reason }
 }
 
-fn reverse_engineer(program: &Vec<u8>) -> Option<u64> {
+//check the program is a single loop that prints one value and then drops
+//a fixed number of bits from A, returns that number of bits
+pub fn analyse_loop(program: &[u8]) -> Result<u32, AocError> {
+    let instructions = program
+        .chunks(2)
+        .enumerate()
+        .map(|(index, instruction)| {
+            let opcode = Opcode::from_value(instruction[0])
+                .ok_or(AocError::InvalidOpcode { day: 17, pointer: index * 2, opcode: instruction[0] })?;
+            let operand = instruction.get(1).ok_or(unsolvable("the program ends in the middle of an instruction"))?;
+            Ok((opcode, *operand))
+        })
+        .collect::<Result<Vec<_>, AocError>>()?;
+
+    let (last, body) = instructions.split_last().ok_or(unsolvable("the program is empty"))?;
+    if *last != (Opcode::Jnz, 0) || body.iter().any(|&(opcode, _)| opcode == Opcode::Jnz) {
+        return Err(unsolvable("the program must be one loop ending with jnz 0"));
+    }
+    if body.iter().filter(|&&(opcode, _)| opcode == Opcode::Out).count() != 1 {
+        return Err(unsolvable("the loop must output exactly one value"));
+    }
+
+    let mut shift = None;
+    let (mut b_written, mut c_written) = (false, false);
+
+    for &(opcode, operand) in body {
+        //registers read by this instruction, before it writes anything
+        let reads_b = opcode == Opcode::Bxl || opcode == Opcode::Bxc || (opcode.takes_combo() && operand == 5);
+        let reads_c = opcode == Opcode::Bxc || (opcode.takes_combo() && operand == 6);
+        if reads_b && !b_written {
+            return Err(unsolvable("the loop reads B before setting it, the output depends on its initial value"));
+        }
+        if reads_c && !c_written {
+            return Err(unsolvable("the loop reads C before setting it, the output depends on its initial value"));
+        }
+
+        match opcode {
+            Opcode::Adv if shift.is_some() => return Err(unsolvable("the loop must shift A only once")),
+            Opcode::Adv if (1..=3).contains(&operand) => shift = Some(operand as u32),
+            Opcode::Adv => return Err(unsolvable("the loop must shift A by a constant from 1 to 3")),
+            Opcode::Bst | Opcode::Bdv => b_written = true,
+            Opcode::Cdv => c_written = true,
+            _ => {}
+        }
+    }
+
+    shift.ok_or(unsolvable("the loop never shifts A, it can't end"))
+}
+
+//lowest A that makes the program output itself
+fn reverse_engineer(device: &Device) -> Result<u64, AocError> {
+    let program = &device.program;
+    let shift = analyse_loop(program)?;
     let mut possible_a = vec![0u64]; 
-    let (instruction, operand) = parse_instruction(&program, 0); //parse the first instruction and operand
 
 
-    //we start by iterating over the program's expected output in reverse order
-    //this ensures that the reverse engineering process starts from the final
-    for &expected_output in program.iter().rev() {
+    //we start by iterating over the program's expected output in reverse order,
+    //each loop consumes `shift` bits of A, so the last output only depends on
+    //the highest bits and every earlier output adds `shift` lower bits
+    for expected_len in 1..=program.len() {
+        let expected_output = &program[program.len() - expected_len..];
 
         //vector to store all possible candidates for register A
         let mut new_possible_a = vec![];
@@ -40,18 +309,18 @@ fn reverse_engineer(program: &Vec<u8>) -> Option<u64> {
         //now we iterate over the currently known possible values for register A
         //from previous iterations
         for &next_a in &possible_a {
-            //each k represents the lower 3 bits of the candidate
-            for k in 0..8 {
-                //calculate the current value of register A
-                //by shifting the previous value left by 3 bits and adding k
-                let current_a = (next_a * 8) + k;
+            //each k represents the lower bits of the candidate
+            for k in 0..1 << shift {
+                let current_a = (next_a << shift) | k;
              
                 //we check if the current value of register A satisfies the program
                 //if it does, we add it to the list of possible candidates
-                let mut output_vec = Vec::new();
-                compute(instruction, operand, &program.to_vec(), current_a, 0, 0, 0, &mut output_vec);
+                let mut computer = Computer::new(
+                    program,
+                    Registers { a: current_a, b: device.b_reg, c: device.c_reg },
+                );
 
-                if output_vec.get(0) == Some(&expected_output) {
+                if computer.run()? == expected_output {
                     new_possible_a.push(current_a);
                 }
 
@@ -61,129 +330,108 @@ fn reverse_engineer(program: &Vec<u8>) -> Option<u64> {
         possible_a = new_possible_a;
     }
 
-    possible_a.into_iter().min()
+    possible_a
+        .into_iter()
+        .min()
+        .ok_or(unsolvable("no value of A makes the program output itself"))
 }
 
-
-pub fn compute<'a>(
-    instruction: u8,
-    mut operand: u64,
-    program: &Vec<u8>,
-    mut a_reg: u64,
-    mut b_reg: u64,
-    mut c_reg: u64,
-    mut pointer: usize,
-    output_vec: &'a mut Vec<u8>,
-) -> &'a Vec<u8> {
-
-    //operand
-    match instruction {
-        0 | 2 | 5 | 6 | 7 => {
-            operand = match operand {
-                0..=3 => operand,
-                4 => a_reg,
-                5 => b_reg,
-                6 => c_reg,
-                _ => panic!("Invalid operand"),
+//one instruction per line, like ` 0: bst A`, values that are not an opcode show as `??`
+pub fn disassemble(program: &[u8]) -> String {
+    program
+        .chunks(2)
+        .enumerate()
+        .map(|(index, instruction)| {
+            let operand = instruction.get(1).copied().unwrap_or_default();
+            let Some(opcode) = Opcode::from_value(instruction[0]) else {
+                return format!("{:2}: ?? {}", index * 2, operand);
             };
-        }
-        _ => {}
-    }
-    
-    //process instruction
-    match instruction {
-        //adv: Perform division
-        0 => {
-            a_reg >>= operand; 
-            pointer += 2; 
-        }
-
-        // bxl: Perform bitwise XOR
-        1 => {
-            b_reg ^= operand; // XOR B with the operand
-            pointer += 2;
-        }
-
-        // bst: Modulo 8 operation and store in B
-        2 => {
-            b_reg = operand & 7; //keep only the lowest 3 bits
-            pointer += 2;
-        }
+            let operand = match (opcode.takes_combo(), operand) {
+                (true, 4) => "A".to_string(),
+                (true, 5) => "B".to_string(),
+                (true, 6) => "C".to_string(),
+                (_, value) => value.to_string(),
+            };
+            format!("{:2}: {} {}", index * 2, opcode.mnemonic(), operand)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-        //jnz
-        3 => {
-            if a_reg != 0 {
-                pointer = operand as usize;
-            } else {
-                pointer += 2;
-            }
-        }
+//turn mnemonics back into the comma separated program, the inverse of disassemble
+//a leading `pointer:` is optional and `;` starts a comment
+pub fn assemble(source: &str) -> Result<String, AocError> {
+    let mut program = Vec::new();
 
-        //bxc
-        4 => {
-            b_reg ^= c_reg; // XOR B with C
-            pointer += 2;
+    for (index, text) in source.lines().enumerate() {
+        let line = Line::new(17, index, text);
+        let code = text.split(';').next().unwrap_or_default();
+        let code = code.split_once(':').map_or(code, |(_, code)| code);
+        let mut tokens = code.split_whitespace();
+
+        let Some(mnemonic) = tokens.next() else {
+            continue;
+        };
+        let opcode = Opcode::from_mnemonic(mnemonic)
+            .ok_or_else(|| line.expected(mnemonic, "a mnemonic like adv or out"))?;
+
+        let operand = match tokens.next() {
+            Some("A") if opcode.takes_combo() => 4,
+            Some("B") if opcode.takes_combo() => 5,
+            Some("C") if opcode.takes_combo() => 6,
+            Some(token) => match line.parse::<u8>(token)? {
+                value @ 0..=7 => value,
+                _ => return Err(line.expected(token, "an operand from 0 to 7")),
+            },
+            //bxc ignores its operand, so it may be left out
+            None if opcode == Opcode::Bxc => 0,
+            None => return Err(line.expected_end("an operand")),
+        };
+
+        if let Some(token) = tokens.next() {
+            return Err(line.expected(token, "the end of the instruction"));
         }
-        //out
-        5 => {
-            let output = operand & 7; 
-            output_vec.push(output as u8); 
-            pointer += 2; 
-        }
-        //bdv
-        6 => {
-            b_reg = a_reg >> operand; //divide B by 2^operand
-            pointer += 2;
-        }
-        //cdv
-        7 => {
-            c_reg = a_reg >> operand; //divide C by 2^operand
-            pointer += 2;
-        }
-        // Halt if an unknown instruction is encountered
-        _ => return output_vec,
-    }
 
-    if pointer >= program.len() {
-        return output_vec;
+        program.push(opcode as u8);
+        program.push(operand);
     }
 
-    let (new_instruction, new_operand) = parse_instruction(&program, pointer); //parse the next instruction and operand
-
-    //continue processing with updated state
-    compute(new_instruction, new_operand, program, a_reg, b_reg, c_reg, pointer, output_vec)
-}
-
-fn parse_instruction(program: &Vec<u8>, pointer: usize) -> (u8, u64) {
-    let new_instruction = program[pointer];
-    let operand = program[pointer + 1] as u64;
-
-    (new_instruction, operand)
+    Ok(program.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(","))
 }
 
-pub fn parse_input(input: &[String]) -> (u64, u64, u64, Vec<u8>) {
+pub fn parse_input(input: &[String]) -> Result<(u64, u64, u64, Vec<u8>), AocError> {
     let mut a_reg = 0;
     let mut b_reg = 0;
     let mut c_reg = 0;
     let mut program = Vec::new();
 
-    for line in input {
-        if line.starts_with("Register A:") {
-            a_reg = line[11..].trim().parse().unwrap();
-        } else if line.starts_with("Register B:") {
-            b_reg = line[11..].trim().parse().unwrap();
-        } else if line.starts_with("Register C:") {
-            c_reg = line[11..].trim().parse().unwrap();
-        } else if line.starts_with("Program:") {
-            program = line[8..]
-                .trim()
+    for (index, text) in input.iter().enumerate() {
+        let line = Line::new(17, index, text);
+        if let Some(value) = text.strip_prefix("Register A:") {
+            a_reg = line.parse(value)?;
+        } else if let Some(value) = text.strip_prefix("Register B:") {
+            b_reg = line.parse(value)?;
+        } else if let Some(value) = text.strip_prefix("Register C:") {
+            c_reg = line.parse(value)?;
+        } else if let Some(value) = text.strip_prefix("Program:") {
+            program = value
                 .split(',')
-                .map(|num| num.trim().parse().unwrap())
-                .collect();
+                .map(|num| match line.parse::<u8>(num)? {
+                    value @ 0..=7 => Ok(value),
+                    _ => Err(line.expected(num.trim(), "a 3-bit number")),
+                })
+                .collect::<Result<Vec<u8>, AocError>>()?;
+
+            //every instruction is an opcode followed by its operand
+            if program.len() % 2 != 0 {
+                return Err(line.expected_end("an operand after the last opcode"));
+            }
+        } else if !text.trim().is_empty() {
+            return Err(line.expected(text, "a register or the program"));
         }
     }
 
-    (a_reg, b_reg, c_reg, program)
+    Ok((a_reg, b_reg, c_reg, program))
 }
 
 
@@ -199,8 +447,8 @@ mod tests {
             "\n",
             "Program: 0,1,5,4,3,0");
     
-        let parsed_input = generate_input(example_input);
-        assert_eq!(solve_part1(&parsed_input), "4,6,3,5,6,3,5,2,1,0"); 
+        let parsed_input = generate_input(example_input).unwrap();
+        assert_eq!(solve_part1(&parsed_input), Ok("4,6,3,5,6,3,5,2,1,0".to_string()));
     }
 
     #[test]
@@ -213,7 +461,86 @@ mod tests {
             "Program: 0,3,5,4,3,0"
         );
     
-        let parsed_input = generate_input(example_input);
-        assert_eq!(solve_part2(&parsed_input), 117440);
+        let parsed_input = generate_input(example_input).unwrap();
+        assert_eq!(solve_part2(&parsed_input), Ok(117440));
+    }
+
+    #[test]
+    fn test_day17_computer_step() {
+        let program = [0, 1, 5, 4, 3, 0];
+        let mut computer = Computer::new(&program, Registers { a: 729, b: 0, c: 0 }).with_trace();
+
+        assert_eq!(computer.step(), Ok(true));
+        assert_eq!((computer.registers.a, computer.pointer), (364, 2));
+        assert_eq!(computer.step(), Ok(true));
+        assert_eq!(computer.output, vec![4]);
+
+        computer.run().unwrap();
+        assert!(computer.halted());
+        assert_eq!(computer.step(), Ok(false));
+        assert_eq!(computer.trace().len(), computer.executed());
+        assert_eq!(
+            computer.trace()[2],
+            TraceEntry { pointer: 4, opcode: 3, operand: 0, registers: Registers { a: 364, b: 0, c: 0 } }
+        );
+    }
+
+    #[test]
+    fn test_day17_disassemble() {
+        let program = [2, 4, 1, 1, 7, 5, 4, 0, 0, 3, 5, 5, 3, 0];
+        let listing = disassemble(&program);
+        assert_eq!(
+            listing,
+            " 0: bst A\n 2: bxl 1\n 4: cdv B\n 6: bxc 0\n 8: adv 3\n10: out B\n12: jnz 0"
+        );
+        assert_eq!(assemble(&listing), Ok("2,4,1,1,7,5,4,0,0,3,5,5,3,0".to_string()));
+        assert_eq!(disassemble(&[9, 1, 5, 4]), " 0: ?? 1\n 2: out A");
+        assert_eq!(assemble("; count down\nadv 1\nout A\nbxc\njnz 0"), Ok("0,1,5,4,4,0,3,0".to_string()));
+
+        assert_eq!(
+            assemble("adv 1\nbxl A"),
+            Err(AocError::InvalidNumber { day: 17, line: 2, column: 5, token: "A".to_string() })
+        );
+        assert_eq!(
+            assemble("mul 2"),
+            Err(AocError::UnexpectedInput { day: 17, line: 1, column: 1, expected: "a mnemonic like adv or out" })
+        );
+    }
+
+    #[test]
+    fn test_day17_quine_analysis() {
+        let device = |program: &str| {
+            generate_input(&format!("Register A: 0\n\nProgram: {}", assemble(program).unwrap())).unwrap()
+        };
+
+        assert_eq!(analyse_loop(&[2, 4, 1, 1, 7, 5, 4, 0, 0, 3, 5, 5, 3, 0]), Ok(3));
+        assert_eq!(analyse_loop(&[0, 3, 5]), Err(unsolvable("the program ends in the middle of an instruction")));
+        let empty = Device { a_reg: 0, b_reg: 0, c_reg: 0, program: Vec::new() };
+        assert_eq!(analyse_loop(&empty.program), Err(unsolvable("the program is empty")));
+        assert_eq!(solve_part2(&empty), Err(unsolvable("the program is empty")));
+        assert_eq!(analyse_loop(&[0, 3, 8, 4, 3, 0]), Err(AocError::InvalidOpcode { day: 17, pointer: 2, opcode: 8 }));
+        assert_eq!(
+            solve_part2(&device("bst A\nadv 1\nout A\njnz 0")),
+            Err(unsolvable("no value of A makes the program output itself"))
+        );
+        assert_eq!(
+            solve_part2(&device("adv 3\nout B\njnz 0")),
+            Err(unsolvable("the loop reads B before setting it, the output depends on its initial value"))
+        );
+        assert_eq!(solve_part2(&device("adv A\nout A\njnz 0")), Err(unsolvable("the loop must shift A by a constant from 1 to 3")));
+        assert_eq!(solve_part2(&device("adv 3\nout A\njnz 2")), Err(unsolvable("the program must be one loop ending with jnz 0")));
+    }
+
+    #[test]
+    fn test_day17_instruction_limit() {
+        //jumps back to itself forever
+        let program = [3, 0];
+        let mut computer = Computer::new(&program, Registers { a: 1, b: 0, c: 0 }).with_limit(50);
+        assert_eq!(computer.run(), Err(AocError::InstructionLimit { day: 17, limit: 50 }));
+        assert_eq!(computer.executed(), 50);
+
+        let program = [1, 2, 8, 0];
+        let mut computer = Computer::new(&program, Registers { a: 1, b: 0, c: 0 });
+        assert_eq!(computer.run(), Err(AocError::InvalidOpcode { day: 17, pointer: 2, opcode: 8 }));
     }
 }
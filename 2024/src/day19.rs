@@ -1,21 +1,23 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::{HashMap};
 
+//available towel patterns and the designs to build
 #[aoc_generator(day19)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> (Vec<String>, Vec<String>) {
+    let lines: Vec<String> = input.lines().map(|line| line.to_string()).collect();
+    process_input(&lines)
 }
 
 #[aoc(day19, part1)]
-pub fn solve_part1(input: &[String]) -> usize {
-    let (patterns, designs) = process_input(input);
-    designs.iter().filter(|design| can_construct_design(&patterns, design)).count()
+pub fn solve_part1(input: &(Vec<String>, Vec<String>)) -> usize {
+    let (patterns, designs) = input;
+    designs.iter().filter(|design| can_construct_design(patterns, design)).count()
 }
 
 #[aoc(day19, part2)]
-pub fn solve_part2(input: &[String]) -> usize {
-    let (patterns, designs) = process_input(input);
-    designs.iter().map(|design| count_ways_to_construct(&patterns, design)).sum()
+pub fn solve_part2(input: &(Vec<String>, Vec<String>)) -> usize {
+    let (patterns, designs) = input;
+    designs.iter().map(|design| count_ways_to_construct(patterns, design)).sum()
 }
 
 fn count_ways_to_construct(patterns: &[String], design: &str) -> usize {
@@ -60,11 +62,11 @@ fn can_construct_design(patterns: &[String], design: &str) -> bool {
         }
 
         for pattern in patterns {
-            if remaining.starts_with(pattern) {
-                if backtrack(patterns, &remaining[pattern.len()..], memo) {
-                    memo.insert(remaining.to_string(), true);
-                    return true;
-                }
+            if remaining.starts_with(pattern.as_str())
+                && backtrack(patterns, &remaining[pattern.len()..], memo)
+            {
+                memo.insert(remaining.to_string(), true);
+                return true;
             }
         }
         memo.insert(remaining.to_string(), false);
@@ -1,93 +1,52 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{HashMap, HashSet, BinaryHeap};
-use std::cmp::Ordering;
-use itertools::Either;
+use std::collections::{HashMap, HashSet};
+use crate::error::{AocError, Line};
+use crate::pathfinding::{self, Path};
+use crate::utils::{Grid, Point};
 
-type Position = (usize, usize);
+type Position = Point;
 
 #[derive(Debug, Clone)]
-pub struct Grid {
-    pub width: usize,
-    pub height: usize,
-    pub obstacles: HashSet<Position>,
+pub struct Racetrack {
+    pub grid: Grid<char>,
     pub start: Position,
     pub end: Position,
 }
 
-fn directions() -> [(isize, isize); 4] {
-    [(0, 1), (0, -1), (1, 0), (-1, 0)]
-}
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct Node {
-    position: Position,
-    cost: usize,
-    priority: usize,
-}
-
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.priority.cmp(&self.priority)
-    }
-}
-
-fn heuristic(a: Position, b: Position) -> usize{
-    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
-}
-
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[aoc_generator(day20)]
-pub fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Racetrack, AocError> {
+    generate_racetrack(input)
 }
 
 #[aoc(day20, part1)]
-pub fn solve_part1(input: &[String]) -> usize {
-    let grid = generate_grid(input);
-    let min_path = a_star(grid.start, grid.end, grid.width, grid.height, &grid.obstacles, true)
-                .and_then(|result| if let Either::Left(path) = result { Some(path) } else { None })
-                .unwrap();
+pub fn solve_part1(racetrack: &Racetrack) -> Result<usize, AocError> {
+    let min_path = race_path(racetrack)?;
     let max_psicoseconds = min_path.len().saturating_sub(1);
     let mut count = 0;
     let mut difference_counts: HashMap<usize, usize> = HashMap::new();
 
-    let obstacles_set: HashSet<_> = grid.obstacles.iter().copied().collect();
-
-    let obstacles_set_filtered: HashSet<Position> = obstacles_set
-        .iter()
-        .cloned()
+    let obstacles_set_filtered: HashSet<Position> = racetrack.grid
+        .find_all(&'#')
         .filter(|&obstacle| {
             let adjacent_points = min_path.iter().filter(|&&path_position| {
-                directions().iter().any(|&(dx, dy)| {
-                    (path_position.0 as isize + dx, path_position.1 as isize + dy) == (obstacle.0 as isize, obstacle.1 as isize)
-                })
+                manhattan_distance(path_position, obstacle) == 1
             }).count();
             adjacent_points >= 2 
         })
         .collect();
     
-    
+    //remove one obstacle at a time and put it back afterwards
+    let mut reduced_grid = racetrack.grid.clone();
     for &obstacle in &obstacles_set_filtered {
         
-        let mut reduced_obstacles = grid.obstacles.clone();
-        reduced_obstacles.remove(&obstacle);
+        reduced_grid[obstacle] = '.';
+        let result = shortest_path(racetrack, &reduced_grid);
+        reduced_grid[obstacle] = '#';
 
-        if let Some(Either::Right(path_length)) = a_star(
-            grid.start,
-            grid.end,
-            grid.width,
-            grid.height,
-            &reduced_obstacles,
-            false,
-        ) {
+        if let Some(Path { cost: path_length, .. }) = result {
             let save = max_psicoseconds.saturating_sub(path_length);
 
-            if save >= 2 && save % 2 == 0{
+            if save >= 2 && save.is_multiple_of(2){
                 *difference_counts.entry(max_psicoseconds - path_length).or_insert(0) += 1;
                 if max_psicoseconds - path_length >= 100 {
                     count += 1;
@@ -96,15 +55,12 @@ pub fn solve_part1(input: &[String]) -> usize {
         }
     }
         
-    count
+    Ok(count)
 }
 
 #[aoc(day20, part2)]
-pub fn solve_part2(input: &[String]) -> usize {
-    let grid = generate_grid(input);
-    let min_path = a_star(grid.start, grid.end, grid.width, grid.height, &grid.obstacles, true)
-        .and_then(|result| if let Either::Left(path) = result { Some(path) } else { None })
-        .unwrap();
+pub fn solve_part2(racetrack: &Racetrack) -> Result<usize, AocError> {
+    let min_path = race_path(racetrack)?;
 
     let original_cost = min_path.len().saturating_sub(1);
     
@@ -124,8 +80,8 @@ pub fn solve_part2(input: &[String]) -> usize {
     let mut difference_counts: HashMap<usize, usize> = HashMap::new();
     let max_cheat_distance = 20;
 
-    for (_i, &pt_a) in min_path.iter().enumerate() {
-        for (_j, &pt_b) in min_path.iter().enumerate() {
+    for &pt_a in min_path.iter() {
+        for &pt_b in min_path.iter() {
             //manhattan distance
             let dist = manhattan_distance(pt_a, pt_b);
             if dist <= max_cheat_distance {
@@ -135,7 +91,7 @@ pub fn solve_part2(input: &[String]) -> usize {
                 let save = original_cost.saturating_sub(new_cost);
 
                 //only consider if it saves at least 50 picoseconds and is multiple of 2, etc.
-                if save >= 50 && save % 2 == 0 {
+                if save >= 50 && save.is_multiple_of(2) {
                     *difference_counts.entry(save).or_insert(0) += 1;
                     if save >= 100 {
                         count += 1;
@@ -152,111 +108,48 @@ pub fn solve_part2(input: &[String]) -> usize {
         println!("There are {} cheats that save {} picoseconds.", cnt, save);
     }*/
 
-    count
+    Ok(count)
 }
 
 fn manhattan_distance(a: Position, b: Position) -> usize {
-    (a.0 as isize - b.0 as isize).abs() as usize
-        + (a.1 as isize - b.1 as isize).abs() as usize
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as usize
 }
 
 
-pub fn generate_grid(input: &[String]) -> Grid {
-    let mut obstacles = HashSet::new();
-    let mut start = None;
-    let mut end = None;
-    let width = input.get(0).map(|line| line.len()).unwrap();
-    let height = input.len();
-
-    for (y, line) in input.iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                '#' => {obstacles.insert((x, y));},
-                'S' => {start = Some((x, y));},
-                'E' => {end = Some((x, y));},
-                _ => {}
-            }
-        }
+pub fn generate_racetrack(input: &str) -> Result<Racetrack, AocError> {
+    if input.is_empty() {
+        return Err(AocError::MissingInput { day: 20, expected: "the racetrack" });
     }
+    let grid = Grid::parse(20, input)?;
 
-    Grid {
-        width,
-        height,
-        obstacles,
-        start: start.unwrap(),
-        end: end.unwrap(),
+    if let Some(((y, x), _)) = grid.iter().find(|(_, c)| !"#.SE".contains(**c)) {
+        let text = input.lines().nth(y as usize).unwrap_or_default();
+        let line = Line::new(20, y as usize, text);
+        return Err(line.expected(line.from_char(x as usize), "one of `#.SE`"));
     }
-}
-
-
-pub fn a_star(
-    start: Position, 
-    end: Position, 
-    width: usize, 
-    height: usize,
-    obstacles: &HashSet<Position>,
-    return_path: bool,
-) -> Option<Either<Vec<Position>,usize>>{
-    let mut open_set = BinaryHeap::new();
-    open_set.push(Node {
-        position: start,
-        cost: 0,
-        priority: heuristic(start, end),
-    });
-
-    let mut g_score = HashMap::new();
-    g_score.insert(start, 0);
-    let mut came_from = HashMap::new();
-
-    while let Some(Node {position, cost, .. }) = open_set.pop() {
-        if position == end {
-            if return_path {
-                let mut path = Vec::new();
-                let mut current = end;
-                path.push(current);
 
-                while let Some(&prev) = came_from.get(&current) {
-                    current = prev;
-                    path.push(current);
-                }
-                path.reverse();
-                return Some(Either::Left(path));
-            } else {
-                return Some(Either::Right(cost));
-            }
-        }
-
-        for (dx, dy) in directions().iter() {
-            let new_position: (usize, usize) = (
-                (position.0 as isize + dx).try_into().unwrap_or(usize::MAX),
-                (position.1 as isize + dy).try_into().unwrap_or(usize::MAX),
-            );
-
-            if new_position.0 > width || new_position.1 > height-1 {
-                continue;
-            }
-
-            let new_position = (new_position.0, new_position.1);
-
-            if obstacles.contains(&new_position) {
-                continue;
-            }
+    Ok(Racetrack {
+        start: grid.find(&'S').ok_or(AocError::MissingInput { day: 20, expected: "the start `S`" })?,
+        end: grid.find(&'E').ok_or(AocError::MissingInput { day: 20, expected: "the end `E`" })?,
+        grid,
+    })
+}
 
-            let new_cost = g_score.get(&position).unwrap_or(&usize::MAX) + 1;
 
-            if new_cost < *g_score.get(&new_position).unwrap_or(&usize::MAX) {
-                g_score.insert(new_position, new_cost);
-                open_set.push(Node {
-                    position: new_position,
-                    cost: new_cost,
-                    priority: new_cost + heuristic(new_position, end),
-                });
-                came_from.insert(new_position, position);
-            }
-        }
-    }
+//the positions of the race without cheating
+fn race_path(racetrack: &Racetrack) -> Result<Vec<Position>, AocError> {
+    shortest_path(racetrack, &racetrack.grid)
+        .map(|path| path.states)
+        .ok_or(AocError::Unsolvable { day: 20, reason: "the end can't be reached from the start" })
+}
 
-    None
+//the track may be a modified copy of the racetrack's grid
+fn shortest_path(racetrack: &Racetrack, track: &Grid<char>) -> Option<Path<Position>> {
+    pathfinding::bfs(
+        racetrack.start,
+        |&position| track.neighbours4(position).filter(|&next| track[next] != '#'),
+        |&position| position == racetrack.end,
+    )
 }
 
 #[cfg(test)]
@@ -282,9 +175,9 @@ mod tests {
             "#...#...#...###\n",
             "###############\n"
         );
-        let input = generate_input(example_input);
+        let input = generate_input(example_input).unwrap();
         let result = solve_part1(&input);
-        assert_eq!(result, 0); 
+        assert_eq!(result, Ok(0));
     }
 
      #[test]
@@ -306,8 +199,24 @@ mod tests {
             "#...#...#...###\n",
             "###############\n"
         );
-        let input = generate_input(example_input);
+        let input = generate_input(example_input).unwrap();
         let result = solve_part2(&input);
-        assert_eq!(result, 0); 
+        assert_eq!(result, Ok(0));
+    }
+
+    #[test]
+    fn test_day20_unreachable_end() {
+        let input = generate_input("#####\n#S#E#\n#####").unwrap();
+        let error = AocError::Unsolvable { day: 20, reason: "the end can't be reached from the start" };
+        assert_eq!(solve_part1(&input), Err(error.clone()));
+        assert_eq!(solve_part2(&input), Err(error));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_day20_invalid_cell() {
+        assert_eq!(
+            generate_input("######\n#Sé.E#\n######").err(),
+            Some(AocError::UnexpectedInput { day: 20, line: 2, column: 3, expected: "one of `#.SE`" })
+        );
+    }
+}
@@ -1,177 +1,277 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use phf::phf_map;
+use crate::error::{AocError, Line};
+use crate::utils::{Grid, Point};
+use std::collections::HashMap;
 
-const NUMERIC_KEYPAD: phf::Map<char, (u32, u32)> = phf::phf_map! {
-    '7' => (0, 0), '8' => (1, 0), '9' => (2, 0),
-    '4' => (0, 1), '5' => (1, 1), '6' => (2, 1),
-    '1' => (0, 2), '2' => (1, 2), '3' => (2, 2),
-                   '0' => (1, 3), 'A' => (2, 3),
-};
+//the keypads as drawn in the puzzle, the arm may never go over the gap
+pub const NUMERIC_LAYOUT: &str = "789\n456\n123\n#0A";
+pub const DIRECTIONAL_LAYOUT: &str = "#^A\n<v>";
+pub const GAP: char = '#';
 
-const ROBOT_KEYPAD: phf::Map<char, (u32, u32)> = phf::phf_map! {
-                   '^' => (1, 0), 'A' => (2, 0),
-    '<' => (0, 1), 'v' => (1, 1), '>' => (2, 1),
-};
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keypad {
+    grid: Grid<char>,
+    gap: char,
+    keys: HashMap<char, Point>,
+}
+
+impl Keypad {
+    //one character per key, `gap` marks the cells without a button
+    pub fn parse(layout: &str, gap: char) -> Result<Keypad, AocError> {
+        let grid = Grid::parse(21, layout)?;
+        let mut keys = HashMap::new();
+
+        for ((row, col), &key) in grid.iter() {
+            if key != gap && keys.insert(key, (row, col)).is_some() {
+                let text = layout.lines().nth(row as usize).unwrap_or_default();
+                let line = Line::new(21, row as usize, text);
+                //grid columns count characters, the text is sliced in bytes
+                let byte = text.char_indices().nth(col as usize).map_or(text.len(), |(byte, _)| byte);
+                return Err(line.expected(&text[byte..], "each key only once"));
+            }
+        }
+
+        //every arm starts on A
+        if !keys.contains_key(&'A') {
+            return Err(AocError::MissingInput { day: 21, expected: "the A key" });
+        }
+
+        Ok(Keypad { grid, gap, keys })
+    }
+
+    //a keypad the robots are driven with, it needs the four arrows
+    pub fn parse_directional(layout: &str, gap: char) -> Result<Keypad, AocError> {
+        let keypad = Keypad::parse(layout, gap)?;
+        if !"^v<>".chars().all(|arrow| keypad.keys.contains_key(&arrow)) {
+            return Err(AocError::MissingInput { day: 21, expected: "the four arrow keys" });
+        }
+        Ok(keypad)
+    }
 
+    pub fn numeric() -> Keypad {
+        Keypad::parse(NUMERIC_LAYOUT, GAP).expect("a valid numeric layout")
+    }
+
+    pub fn directional() -> Keypad {
+        Keypad::parse_directional(DIRECTIONAL_LAYOUT, GAP).expect("a valid directional layout")
+    }
+
+    pub fn position(&self, key: char) -> Option<Point> {
+        self.keys.get(&key).copied()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = char> + '_ {
+        self.keys.keys().copied()
+    }
+
+    //cells of the layout without a button
+    pub fn gaps(&self) -> impl Iterator<Item = Point> + '_ {
+        self.grid.find_all(&self.gap)
+    }
+
+    //the arm may rest on this cell
+    pub fn is_key(&self, position: Point) -> bool {
+        self.grid.get(position).is_some_and(|&key| key != self.gap)
+    }
+}
 
+//door codes, every character must be a key of the numeric keypad
 #[aoc_generator(day21)]
-fn generate_input(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_string()).collect()
+pub fn generate_input(input: &str) -> Result<Vec<String>, AocError> {
+    let numeric = Keypad::numeric();
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, text)| {
+            let line = Line::new(21, index, text);
+            match text.char_indices().find(|&(_, ch)| numeric.position(ch).is_none()) {
+                Some((column, _)) => Err(line.expected(&text[column..], "a numeric keypad key")),
+                None => Ok(text.to_string()),
+            }
+        })
+        .collect()
 }
 
+//press count of every move between two keys, the arm starts on the first key
+//and ends pressing the second one. pairs the arm can't move between are left out
+type Costs = HashMap<(char, char), u64>;
+
 #[aoc(day21, part1)]
-fn solve_part1(_input: &[String]) -> u32 {
-    let numeric_keypad_start = (2, 3); 
-    let robot1_keypad_start = (1, 0);
-    let robot2_keypad_start = (1, 0); 
+pub fn solve_part1(input: &[String]) -> Result<u64, AocError> {
+    complexity_sum(input, &Keypad::numeric(), &Keypad::directional(), 2)
+}
+
+#[aoc(day21, part2)]
+pub fn solve_part2(input: &[String]) -> Result<u64, AocError> {
+    complexity_sum(input, &Keypad::numeric(), &Keypad::directional(), 25)
+}
 
-    let keypads = [
-        (&NUMERIC_KEYPAD, numeric_keypad_start),
-        (&ROBOT_KEYPAD, robot1_keypad_start),
-        (&ROBOT_KEYPAD, robot2_keypad_start),
-    ];
+//`robots` directional keypads sit between the human and the door keypad
+pub fn complexity_sum(codes: &[String], door: &Keypad, directional: &Keypad, robots: usize) -> Result<u64, AocError> {
+    let tables = directional_costs(directional, robots);
+    let costs = layer_costs(door, tables.last().unwrap());
 
-    let result =compute_full_sequence("029A", &keypads);    
-    println!("The full sequence is: {}", result);
-    0 
+    codes.iter().enumerate().try_fold(0u64, |total, (index, code)| {
+        check_code(code, index, door)?;
+        let numeric: u64 = code.chars().filter(char::is_ascii_digit).collect::<String>().parse().unwrap_or(0);
+        press_cost(code, &costs)
+            .and_then(|presses| presses.checked_mul(numeric))
+            .and_then(|complexity| total.checked_add(complexity))
+            .ok_or(AocError::Unsolvable { day: 21, reason: "a code can't be typed without going over a gap" })
+    })
 }
 
-#[aoc(day21, part2)]
-fn solve_part2(_input: &[String]) -> u32 {
-    0
-}
-
-fn compute_full_sequence(
-    input: &str,
-    keypads: &[(&phf::Map<char, (u32, u32)>, (u32, u32))],
-) -> String {
-    let mut pos = keypads.iter().map(|k| k.1).collect::<Vec<_>>();
-    let mut ans = String::new();
-    for ch in input.chars() {
-        if let Some(seq) = recurse(&mut pos, ch, keypads, 0) {
-            ans.push_str(&seq);
-        }
+//every character of the code must be a key of the door keypad
+fn check_code(code: &str, index: usize, door: &Keypad) -> Result<(), AocError> {
+    match code.char_indices().find(|&(_, ch)| door.position(ch).is_none()) {
+        Some((column, _)) => Err(Line::new(21, index, code).expected(&code[column..], "a key of the door keypad")),
+        None => Ok(()),
     }
-    ans
-}
-
-fn recurse(
-    positions: &mut [(u32, u32)],
-    target: char,
-    keypads: &[(&phf::Map<char, (u32, u32)>, (u32, u32))],
-    level: usize,
-) -> Option<String> {
-    if level >= keypads.len() {
-        return None;
-    }
-    let (map, _) = keypads[level];
-    if let Some(&goal) = map.get(&target) {
-        if level == keypads.len() - 1 {
-            let route = safe_move(positions[level], goal, map)?;
-            positions[level] = goal;
-            let mut out = route;
-            out.push('A');
-            Some(out)
-        } else {
-            let path = safe_move(positions[level], goal, map)?;
-            let mut big = String::new();
-            for c in path.chars() {
-                if let Some(sub) = recurse(positions, c, keypads, level + 1) {
-                    big.push_str(&sub);
-                } else {
-                    return None;
-                }
-            }
-            if let Some(sub) = recurse(positions, 'A', keypads, level + 1) {
-                big.push_str(&sub);
-            } else {
-                return None;
-            }
-            positions[level] = goal;
-            Some(big)
-        }
-    } else {
-        recurse(positions, target, keypads, level + 1)
+}
+
+//tables[0] is the human pressing directly, tables[n] a keypad behind n robots
+fn directional_costs(directional: &Keypad, robots: usize) -> Vec<Costs> {
+    let mut tables = vec![directional
+        .keys()
+        .flat_map(|from| directional.keys().map(move |to| ((from, to), 1)))
+        .collect::<Costs>()];
+
+    for _ in 0..robots {
+        let next = layer_costs(directional, tables.last().unwrap());
+        tables.push(next);
     }
+    tables
 }
 
-fn safe_move(
-    start: (u32, u32),
-    end: (u32, u32),
-    map: &phf::Map<char, (u32, u32)>,
-) -> Option<String> {
-    let mut best = None;
-    for &(dy_first, dx_first) in &[(true, false), (false, true)] {
-        if let Some(r) = route_2phase(start, end, dy_first, dx_first, map) {
-            if best.as_ref().map_or(true, |b: &String| r.len() < b.len()) {
-                best = Some(r);
+//cheapest way to move between each pair of keys of this keypad,
+//when its arm is driven through a keypad with the lower costs
+fn layer_costs(keypad: &Keypad, lower: &Costs) -> Costs {
+    let mut costs = Costs::new();
+    for (&from, &start) in &keypad.keys {
+        for (&to, &end) in &keypad.keys {
+            let cost = safe_move(start, end, keypad)
+                .iter()
+                .filter_map(|route| press_cost(route, lower))
+                .min();
+            if let Some(cost) = cost {
+                costs.insert((from, to), cost);
             }
         }
     }
-    best
+    costs
 }
 
-fn route_2phase(
-    start: (u32, u32),
-    end: (u32, u32),
-    vertical_first: bool,
-    horizontal_first: bool,
-    map: &phf::Map<char, (u32, u32)>,
-) -> Option<String> {
-    let (sx, sy) = (start.0 as i32, start.1 as i32);
-    let (ex, ey) = (end.0 as i32, end.1 as i32);
-    let (mut x, mut y) = (sx, sy);
-    let mut r = String::new();
+//presses needed to type the sequence, starting from A, None if it can't be typed
+fn press_cost(sequence: &str, costs: &Costs) -> Option<u64> {
+    pairs(sequence).try_fold(0u64, |total, pair| total.checked_add(*costs.get(&pair)?))
+}
 
-    let mut moves = Vec::new();
-    if vertical_first {
-        while y < ey { moves.push('v'); y += 1; }
-        while y > ey { moves.push('^'); y -= 1; }
-    }
-    if horizontal_first {
-        while x < ex { moves.push('>'); x += 1; }
-        while x > ex { moves.push('<'); x -= 1; }
+fn pairs(sequence: &str) -> impl Iterator<Item = (char, char)> + '_ {
+    std::iter::once('A').chain(sequence.chars()).zip(sequence.chars())
+}
+
+//the human's presses for one code, only practical for a few robots
+pub fn compute_full_sequence(code: &str, door: &Keypad, directional: &Keypad, robots: usize) -> Result<String, AocError> {
+    check_code(code, 0, door)?;
+    let tables = directional_costs(directional, robots);
+    expand(code, door, directional, &tables)
+        .ok_or(AocError::Unsolvable { day: 21, reason: "a code can't be typed without going over a gap" })
+}
+
+//pick the cheapest route for each key and expand it on the keypad below
+fn expand(sequence: &str, keypad: &Keypad, directional: &Keypad, tables: &[Costs]) -> Option<String> {
+    let Some((lower, rest)) = tables.split_last() else {
+        return Some(sequence.to_string());
+    };
+
+    pairs(sequence)
+        .map(|(from, to)| {
+            let route = safe_move(keypad.position(from)?, keypad.position(to)?, keypad)
+                .into_iter()
+                .filter_map(|route| Some((press_cost(&route, lower)?, route)))
+                .min_by_key(|(cost, _)| *cost)?
+                .1;
+            expand(&route, directional, directional, rest)
+        })
+        .collect()
+}
+
+//routes moving in straight lines, then pressing, that avoid the gaps
+fn safe_move(start: Point, end: Point, keypad: &Keypad) -> Vec<String> {
+    let mut routes = Vec::new();
+    for vertical_first in [true, false] {
+        if let Some(mut route) = route_2phase(start, end, vertical_first, keypad) {
+            route.push('A');
+            if !routes.contains(&route) {
+                routes.push(route);
+            }
+        }
     }
-    if !vertical_first {
-        while y < ey { moves.push('v'); y += 1; }
-        while y > ey { moves.push('^'); y -= 1; }
+    routes
+}
+
+//the inverse of compute_full_sequence: replay the human's presses through
+//every robot and return what gets typed on the door keypad
+pub fn decode(presses: &str, door: &Keypad, directional: &Keypad, robots: usize) -> Result<String, AocError> {
+    let line = Line::new(21, 0, presses);
+    if let Some((column, _)) = presses.char_indices().find(|(_, ch)| !"<>^vA".contains(*ch)) {
+        return Err(line.expected(&presses[column..], "one of `<>^vA`"));
     }
-    if !horizontal_first {
-        while x < ex { moves.push('>'); x += 1; }
-        while x > ex { moves.push('<'); x -= 1; }
+
+    let mut sequence = presses.to_string();
+    for layer in 0..robots {
+        sequence = decode_layer(&sequence, directional, layer)?;
     }
+    decode_layer(&sequence, door, robots)
+}
 
-    for c in moves {
-        let mut nx = x;
-        let mut ny = y;
+//move the arm of one keypad, each A presses the key under it
+fn decode_layer(presses: &str, keypad: &Keypad, layer: usize) -> Result<String, AocError> {
+    let (mut row, mut col) = keypad.keys[&'A'];
+    let mut typed = String::new();
+
+    for (index, c) in presses.chars().enumerate() {
         match c {
-            '^' => ny -= 1,
-            'v' => ny += 1,
-            '<' => nx -= 1,
-            '>' => nx += 1,
-            _ => {}
+            '^' => row -= 1,
+            'v' => row += 1,
+            '<' => col -= 1,
+            '>' => col += 1,
+            _ => {
+                typed.push(keypad.grid[(row, col)]);
+                continue;
+            }
         }
-        if !is_valid(map, nx, ny) {
-            return None;
+        if !keypad.is_key((row, col)) {
+            return Err(AocError::ArmOverGap { day: 21, layer, press: index + 1 });
         }
-        r.push(c);
-        x = nx;
-        y = ny;
     }
-    Some(r)
+    Ok(typed)
 }
 
-fn is_valid(
-    map: &phf::Map<char, (u32, u32)>,
-    xx: i32,
-    yy: i32,
-) -> bool {
-    for (&ch, &(cx, cy)) in map.entries() {
-        if cx as i32 == xx && cy as i32 == yy && ch != ' ' {
-            return true;
+fn route_2phase(start: Point, end: Point, vertical_first: bool, keypad: &Keypad) -> Option<String> {
+    let (mut row, mut col) = start;
+
+    let vertical = if end.0 > row { "v" } else { "^" }.repeat(end.0.abs_diff(row) as usize);
+    let horizontal = if end.1 > col { ">" } else { "<" }.repeat(end.1.abs_diff(col) as usize);
+    let moves = if vertical_first {
+        vertical + &horizontal
+    } else {
+        horizontal + &vertical
+    };
+
+    //every cell the arm goes over must be a key
+    for c in moves.chars() {
+        match c {
+            '^' => row -= 1,
+            'v' => row += 1,
+            '<' => col -= 1,
+            _ => col += 1,
+        }
+        if !keypad.is_key((row, col)) {
+            return None;
         }
     }
-    false
+    Some(moves)
 }
 
 
@@ -188,15 +288,99 @@ mod tests {
             "456A\n",
             "379A\n"
         );
-        let input = generate_input(example_input);
+        let input = generate_input(example_input).unwrap();
         let result = solve_part1(&input);
-        assert_eq!(result, 126384);
+        assert_eq!(result, Ok(126384));
+    }
+
+    #[test]
+    fn test_day21_full_sequence() {
+        let (numeric, directional) = (Keypad::numeric(), Keypad::directional());
+        let sequence = compute_full_sequence("029A", &numeric, &directional, 2).unwrap();
+        assert_eq!(sequence.len(), 68);
+        assert_eq!(compute_full_sequence("029A", &numeric, &directional, 0), Ok("<A^A^^>AvvvA".to_string()));
+    }
+
+    #[test]
+    fn test_day21_decode() {
+        let (numeric, directional) = (Keypad::numeric(), Keypad::directional());
+        let example = "<vA<AA>>^AvAA<^A>A<v<A>>^AvA^A<vA>^A<v<A>^A>AAvA^A<v<A>A>^AAAvA<^A>A";
+        assert_eq!(decode(example, &numeric, &directional, 2), Ok("029A".to_string()));
+
+        for code in ["980A", "179A", "456A", "379A"] {
+            let sequence = compute_full_sequence(code, &numeric, &directional, 3).unwrap();
+            assert_eq!(decode(&sequence, &numeric, &directional, 3), Ok(code.to_string()));
+        }
+
+        assert_eq!(decode("<<A", &numeric, &directional, 0), Err(AocError::ArmOverGap { day: 21, layer: 0, press: 2 }));
+        assert_eq!(decode("<<A", &numeric, &directional, 1), Err(AocError::ArmOverGap { day: 21, layer: 0, press: 2 }));
+        assert_eq!(decode("v<<AA", &numeric, &directional, 1), Err(AocError::ArmOverGap { day: 21, layer: 1, press: 2 }));
+        assert_eq!(
+            decode("<x", &numeric, &directional, 1),
+            Err(AocError::UnexpectedInput { day: 21, line: 1, column: 2, expected: "one of `<>^vA`" })
+        );
+    }
+
+    #[test]
+    fn test_day21_keypad_layouts() {
+        let numeric = Keypad::numeric();
+        assert_eq!(numeric.position('0'), Some((3, 1)));
+        assert_eq!(numeric.gaps().collect::<Vec<_>>(), vec![(3, 0)]);
+        assert_eq!(safe_move((3, 1), (2, 0), &numeric), vec!["^<A".to_string()]);
+
+        //the same keys with the gap in the top right corner
+        let mirrored = Keypad::parse("987\n654\n321\nA0#", GAP).unwrap();
+        assert_eq!(safe_move((0, 0), (2, 2), &mirrored), vec!["vv>>A".to_string(), ">>vvA".to_string()]);
+        assert_eq!(safe_move((3, 0), (0, 2), &mirrored), vec!["^^^>>A".to_string()]);
+        assert_eq!(safe_move((3, 1), (2, 2), &mirrored), vec!["^>A".to_string()]);
+        let codes = vec!["029A".to_string()];
+        assert!(complexity_sum(&codes, &mirrored, &Keypad::directional(), 2).unwrap() > 0);
+
+        assert_eq!(
+            Keypad::parse("12\n21", GAP),
+            Err(AocError::UnexpectedInput { day: 21, line: 2, column: 1, expected: "each key only once" })
+        );
+        assert_eq!(Keypad::parse("#1", GAP), Err(AocError::MissingInput { day: 21, expected: "the A key" }));
+        assert_eq!(
+            Keypad::parse("é1\n2é\nA#", GAP),
+            Err(AocError::UnexpectedInput { day: 21, line: 2, column: 2, expected: "each key only once" })
+        );
+    }
+
+    #[test]
+    fn test_day21_unusable_layouts() {
+        let directional = Keypad::directional();
+        let unsolvable = AocError::Unsolvable { day: 21, reason: "a code can't be typed without going over a gap" };
+
+        //both ways from A to 1 cross a gap
+        let cornered = Keypad::parse("1#\n#A", GAP).unwrap();
+        let codes = vec!["1A".to_string()];
+        assert_eq!(complexity_sum(&codes, &cornered, &directional, 2), Err(unsolvable.clone()));
+        assert_eq!(compute_full_sequence("1A", &cornered, &directional, 1), Err(unsolvable));
+
+        assert_eq!(
+            Keypad::parse_directional("<A>", GAP),
+            Err(AocError::MissingInput { day: 21, expected: "the four arrow keys" })
+        );
+
+        let codes = vec!["029A".to_string(), "12B".to_string()];
+        assert_eq!(
+            complexity_sum(&codes, &Keypad::numeric(), &directional, 2),
+            Err(AocError::UnexpectedInput { day: 21, line: 2, column: 3, expected: "a key of the door keypad" })
+        );
     }
 
     #[test]
     fn test_day21_part2() {
-        let example_input = "";
-        let input = generate_input(example_input);
-        assert_eq!(solve_part2(&input), 0); 
+        let example_input = concat!(
+            "029A\n",
+            "980A\n",
+            "179A\n",
+            "456A\n",
+            "379A\n"
+        );
+        let input = generate_input(example_input).unwrap();
+        assert_eq!(solve_part2(&input), Ok(154115708116294));
+        assert_eq!(solve_part2(&generate_input("").unwrap()), Ok(0));
     }
 }
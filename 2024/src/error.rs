@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+//lines and columns are 1-based, like an editor shows them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AocError {
+    //a token that should be a number
+    InvalidNumber {
+        day: u8,
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    //a line that does not have the expected shape
+    UnexpectedInput {
+        day: u8,
+        line: usize,
+        column: usize,
+        expected: &'static str,
+    },
+    //something the puzzle needs is not in the input at all
+    MissingInput { day: u8, expected: &'static str },
+    //day17 combo operand 7 is reserved
+    InvalidOperand { day: u8, pointer: usize, operand: u64 },
+    //day17 opcodes are 3-bit values
+    InvalidOpcode { day: u8, pointer: usize, opcode: u8 },
+    //day17 program still running after the allowed number of instructions
+    InstructionLimit { day: u8, limit: usize },
+    //the input is well formed but the puzzle can't be solved for it
+    Unsolvable { day: u8, reason: &'static str },
+    //day21 arm moved over a gap or off its keypad, layer 0 is driven by the human
+    ArmOverGap { day: u8, layer: usize, press: usize },
+}
+
+impl AocError {
+    pub fn day(&self) -> u8 {
+        match self {
+            AocError::InvalidNumber { day, .. }
+            | AocError::UnexpectedInput { day, .. }
+            | AocError::MissingInput { day, .. }
+            | AocError::InvalidOperand { day, .. }
+            | AocError::InvalidOpcode { day, .. }
+            | AocError::InstructionLimit { day, .. }
+            | AocError::Unsolvable { day, .. }
+            | AocError::ArmOverGap { day, .. } => *day,
+        }
+    }
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::InvalidNumber { day, line, column, token } => write!(
+                f,
+                "day {}, line {}, column {}: invalid number `{}`",
+                day, line, column, token
+            ),
+            AocError::UnexpectedInput { day, line, column, expected } => write!(
+                f,
+                "day {}, line {}, column {}: expected {}",
+                day, line, column, expected
+            ),
+            AocError::MissingInput { day, expected } => {
+                write!(f, "day {}: missing {}", day, expected)
+            }
+            AocError::InvalidOperand { day, pointer, operand } => write!(
+                f,
+                "day {}, instruction {}: invalid combo operand {}",
+                day, pointer, operand
+            ),
+            AocError::InvalidOpcode { day, pointer, opcode } => write!(
+                f,
+                "day {}, instruction {}: invalid opcode {}",
+                day, pointer, opcode
+            ),
+            AocError::InstructionLimit { day, limit } => {
+                write!(f, "day {}: program did not halt after {} instructions", day, limit)
+            }
+            AocError::Unsolvable { day, reason } => write!(f, "day {}: {}", day, reason),
+            AocError::ArmOverGap { day, layer, press } => write!(
+                f,
+                "day {}, layer {}, press {}: the arm is not over a key",
+                day, layer, press
+            ),
+        }
+    }
+}
+
+impl Error for AocError {}
+
+//a line of the input together with its position, used to build errors
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub day: u8,
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    //index is 0-based, as given by enumerate
+    pub fn new(day: u8, index: usize, text: &'a str) -> Self {
+        Line {
+            day,
+            number: index + 1,
+            text,
+        }
+    }
+
+    //column of a token sliced out of this line, 1 if it comes from elsewhere
+    pub fn column(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let position = token.as_ptr() as usize;
+
+        if position >= start && position <= start + self.text.len() {
+            position - start + 1
+        } else {
+            1
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self, token: &str) -> Result<T, AocError> {
+        token.trim().parse().map_err(|_| AocError::InvalidNumber {
+            day: self.day,
+            line: self.number,
+            column: self.column(token.trim_start()),
+            token: token.trim().to_string(),
+        })
+    }
+
+    //parse the next token of a split, failing if there is none
+    pub fn parse_next<T: FromStr>(
+        &self,
+        tokens: &mut dyn Iterator<Item = &'a str>,
+        expected: &'static str,
+    ) -> Result<T, AocError> {
+        match tokens.next() {
+            Some(token) => self.parse(token),
+            None => Err(self.expected_end(expected)),
+        }
+    }
+
+    //the rest of the line from a character index, as grids count their columns
+    pub fn from_char(&self, index: usize) -> &'a str {
+        let byte = self.text.char_indices().nth(index).map_or(self.text.len(), |(byte, _)| byte);
+        &self.text[byte..]
+    }
+
+    pub fn expected(&self, token: &str, expected: &'static str) -> AocError {
+        AocError::UnexpectedInput {
+            day: self.day,
+            line: self.number,
+            column: self.column(token),
+            expected,
+        }
+    }
+
+    //the line ended before the expected token
+    pub fn expected_end(&self, expected: &'static str) -> AocError {
+        AocError::UnexpectedInput {
+            day: self.day,
+            line: self.number,
+            column: self.text.len() + 1,
+            expected,
+        }
+    }
+}
+
+pub fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(move |(index, text)| Line::new(day, index, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_errors() {
+        let line = Line::new(1, 2, "12   x4");
+        let mut tokens = line.text.split_whitespace();
+
+        assert_eq!(line.parse_next::<i32>(&mut tokens, "a number"), Ok(12));
+        assert_eq!(
+            line.parse_next::<i32>(&mut tokens, "a number"),
+            Err(AocError::InvalidNumber { day: 1, line: 3, column: 6, token: "x4".to_string() })
+        );
+        assert_eq!(
+            line.parse_next::<i32>(&mut tokens, "a number"),
+            Err(AocError::UnexpectedInput { day: 1, line: 3, column: 8, expected: "a number" })
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = AocError::InvalidNumber { day: 5, line: 7, column: 4, token: "1x".to_string() };
+        assert_eq!(error.to_string(), "day 5, line 7, column 4: invalid number `1x`");
+        assert_eq!(error.day(), 5);
+    }
+}
@@ -1,3 +1,117 @@
+use advent_of_code_2024::registry::{self, Solver};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "\
+usage:
+    advent_of_code_2024 list
+    advent_of_code_2024 run <day> [part] [--input <file>|-]
+
+when no part is given every part of the day is run
+the input defaults to input/2024/day<day>.txt, use - to read from stdin";
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("list") => {
+            list();
+            Ok(())
+        }
+        Some("run") => run(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn list() {
+    for solver in registry::solvers() {
+        println!("day {:>2}: {}", solver.day(), solver.title());
+        println!("    input: {}", solver.input_type());
+        for part in solver.parts() {
+            println!("    part {}: {}", part.part, part.answer_type);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut input_path = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--input" | "-i" => {
+                input_path = Some(iter.next().ok_or("missing value for --input")?.clone());
+            }
+            //a bare - reads the input from stdin, like `--input -`
+            "-" => input_path = Some(arg.clone()),
+            _ => positional.push(arg),
+        }
+    }
+
+    let day = match positional.first() {
+        Some(day) => parse_number(day, "day")?,
+        None => return Err(USAGE.to_string()),
+    };
+    let solver = registry::solver(day)
+        .ok_or_else(|| format!("day {} is not registered, see `list`", day))?;
+
+    let parts = match positional.get(1) {
+        Some(part) => {
+            let part = parse_number(part, "part")?;
+            if !solver.parts().iter().any(|info| info.part == part) {
+                return Err(format!("day {} has no part {}", day, part));
+            }
+            vec![part]
+        }
+        None => solver.parts().iter().map(|info| info.part).collect(),
+    };
+
+    let input_path = input_path.unwrap_or_else(|| format!("input/2024/day{}.txt", day));
+    let input = read_input(&input_path)?;
+
+    print_run(solver, &input, &parts)
+}
+
+fn print_run(solver: &dyn Solver, input: &str, parts: &[u8]) -> Result<(), String> {
+    let run = solver.run(input.trim_end(), parts).map_err(|err| err.to_string())?;
+
+    println!("day {}: {}", solver.day(), solver.title());
+    println!("    generator: {:?}", run.generator);
+    for answer in run.answers {
+        match answer.value {
+            Ok(value) => println!("part {}: {}", answer.part, value),
+            Err(err) => println!("part {}: error: {}", answer.part, err),
+        }
+        println!("    solver: {:?}", answer.duration);
+    }
+
+    Ok(())
+}
+
+fn parse_number(value: &str, name: &str) -> Result<u8, String> {
+    value
+        .trim_start_matches("day")
+        .trim_start_matches("part")
+        .parse()
+        .map_err(|_| format!("invalid {}: {}", name, value))
+}
+
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|err| format!("could not read stdin: {}", err))?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path, err))
+    }
 }
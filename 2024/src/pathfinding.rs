@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+//a path from the start to a goal, both included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S> {
+    pub cost: usize,
+    pub states: Vec<S>,
+}
+
+//every shortest path to the cheapest goals, kept as a predecessor graph
+#[derive(Debug, Clone)]
+pub struct AllPaths<S> {
+    pub cost: usize,
+    pub goals: Vec<S>,
+    predecessors: HashMap<S, Vec<S>>,
+}
+
+impl<S: Clone + Eq + Hash> AllPaths<S> {
+    //every state lying on at least one shortest path
+    pub fn states(&self) -> HashSet<S> {
+        let mut seen: HashSet<S> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+
+        while let Some(state) = stack.pop() {
+            for previous in self.predecessors.get(&state).into_iter().flatten() {
+                if seen.insert(previous.clone()) {
+                    stack.push(previous.clone());
+                }
+            }
+        }
+
+        seen
+    }
+}
+
+//heap entry, reversed so the heap pops the lowest priority first
+struct Entry<S> {
+    priority: usize,
+    cost: usize,
+    state: S,
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.cost == other.cost
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority).then(self.cost.cmp(&other.cost))
+    }
+}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn rebuild<S: Clone + Eq + Hash>(goal: S, came_from: &HashMap<S, S>) -> Vec<S> {
+    let mut states = vec![goal];
+    while let Some(previous) = came_from.get(states.last().unwrap()) {
+        states.push(previous.clone());
+    }
+    states.reverse();
+    states
+}
+
+//unweighted search, every move costs 1
+pub fn bfs<S, N, I, G>(start: S, mut neighbours: N, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: Fn(&S) -> bool,
+{
+    let mut came_from = HashMap::new();
+    let mut visited = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(state) = queue.pop_front() {
+        if is_goal(&state) {
+            let states = rebuild(state, &came_from);
+            return Some(Path { cost: states.len() - 1, states });
+        }
+
+        for next in neighbours(&state) {
+            if visited.insert(next.clone()) {
+                came_from.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+//the heuristic must never overestimate the remaining cost
+pub fn a_star<S, N, I, H, G>(start: S, mut neighbours: N, heuristic: H, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    H: Fn(&S) -> usize,
+    G: Fn(&S) -> bool,
+{
+    let mut g_score = HashMap::from([(start.clone(), 0)]);
+    let mut came_from = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+    open_set.push(Entry { priority: heuristic(&start), cost: 0, state: start });
+
+    while let Some(Entry { cost, state, .. }) = open_set.pop() {
+        if is_goal(&state) {
+            return Some(Path { cost, states: rebuild(state, &came_from) });
+        }
+
+        //an outdated entry, the state was reached more cheaply since
+        if cost > g_score[&state] {
+            continue;
+        }
+
+        for (next, step) in neighbours(&state) {
+            let tentative = cost + step;
+            if g_score.get(&next).is_none_or(|&best| tentative < best) {
+                g_score.insert(next.clone(), tentative);
+                came_from.insert(next.clone(), state.clone());
+                open_set.push(Entry { priority: tentative + heuristic(&next), cost: tentative, state: next });
+            }
+        }
+    }
+
+    None
+}
+
+pub fn dijkstra<S, N, I, G>(start: S, neighbours: N, is_goal: G) -> Option<Path<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    G: Fn(&S) -> bool,
+{
+    a_star(start, neighbours, |_| 0, is_goal)
+}
+
+//dijkstra that keeps every predecessor reaching a state at its best cost
+pub fn all_shortest_paths<S, N, I, G>(start: S, mut neighbours: N, is_goal: G) -> Option<AllPaths<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    G: Fn(&S) -> bool,
+{
+    let mut g_score = HashMap::from([(start.clone(), 0)]);
+    let mut predecessors: HashMap<S, Vec<S>> = HashMap::new();
+    let mut open_set = BinaryHeap::new();
+    let mut best: Option<AllPaths<S>> = None;
+    open_set.push(Entry { priority: 0, cost: 0, state: start });
+
+    while let Some(Entry { cost, state, .. }) = open_set.pop() {
+        if best.as_ref().is_some_and(|best| cost > best.cost) {
+            break;
+        }
+        if cost > g_score[&state] {
+            continue;
+        }
+
+        if is_goal(&state) {
+            best.get_or_insert_with(|| AllPaths { cost, goals: Vec::new(), predecessors: HashMap::new() })
+                .goals
+                .push(state);
+            continue;
+        }
+
+        for (next, step) in neighbours(&state) {
+            let tentative = cost + step;
+            match g_score.get(&next) {
+                Some(&known) if tentative > known => {}
+                Some(&known) if tentative == known => {
+                    predecessors.entry(next).or_default().push(state.clone());
+                }
+                _ => {
+                    g_score.insert(next.clone(), tentative);
+                    predecessors.insert(next.clone(), vec![state.clone()]);
+                    open_set.push(Entry { priority: tentative, cost: tentative, state: next });
+                }
+            }
+        }
+    }
+
+    best.map(|best| AllPaths { predecessors, ..best })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a line of numbers where each one may step to the next one or jump two ahead
+    fn steps(&n: &u32) -> Vec<(u32, usize)> {
+        vec![(n + 1, 1), (n + 2, 2)]
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(0u32, |&n| [n + 1, n * 2], |&n| n == 10).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(path.states, vec![0, 1, 2, 4, 5, 10]);
+        assert!(bfs(0u32, |&n| if n < 6 { vec![n + 2] } else { vec![] }, |&n| n == 3).is_none());
+    }
+
+    #[test]
+    fn test_dijkstra_and_a_star() {
+        let path = dijkstra(0, steps, |&n| n == 5).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(path.states.first(), Some(&0));
+        assert_eq!(path.states.last(), Some(&5));
+
+        let path = a_star(0, steps, |&n| 5u32.saturating_sub(n) as usize, |&n| n == 5).unwrap();
+        assert_eq!(path.cost, 5);
+        assert!(dijkstra(0, |&n: &u32| if n < 3 { vec![(n + 1, 1)] } else { vec![] }, |&n| n == 5).is_none());
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        let paths = all_shortest_paths(0, steps, |&n| n == 3).unwrap();
+        assert_eq!(paths.cost, 3);
+        assert_eq!(paths.goals, vec![3]);
+        assert_eq!(paths.states(), HashSet::from([0, 1, 2, 3]));
+    }
+}
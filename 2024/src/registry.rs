@@ -0,0 +1,220 @@
+use crate::*;
+use crate::error::AocError;
+use std::any::type_name;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+//a solved day, independent of the type its generator produces
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn input_type(&self) -> &'static str;
+    fn parts(&self) -> Vec<PartInfo>;
+    fn run(&self, input: &str, parts: &[u8]) -> Result<Run, AocError>;
+}
+
+//what a part may return: a plain answer or one that can fail
+pub trait IntoAnswer {
+    type Value: Display;
+
+    fn into_answer(self) -> Result<Self::Value, AocError>;
+}
+
+macro_rules! plain_answer {
+    ($($answer:ty),*) => {
+        $(
+            impl IntoAnswer for $answer {
+                type Value = $answer;
+
+                fn into_answer(self) -> Result<Self::Value, AocError> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+plain_answer!(i32, i64, u16, u32, u64, u128, usize, String);
+
+impl<T: Display> IntoAnswer for Result<T, AocError> {
+    type Value = T;
+
+    fn into_answer(self) -> Result<Self::Value, AocError> {
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartInfo {
+    pub part: u8,
+    pub answer_type: &'static str,
+}
+
+#[derive(Debug)]
+pub struct Answer {
+    pub part: u8,
+    pub value: Result<String, AocError>,
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+pub struct Run {
+    pub generator: Duration,
+    pub answers: Vec<Answer>,
+}
+
+pub struct Day<T, P1, P2> {
+    pub day: u8,
+    pub title: &'static str,
+    pub generator: fn(&str) -> Result<T, AocError>,
+    pub part1: fn(&T) -> P1,
+    pub part2: fn(&T) -> P2,
+}
+
+impl<T, P1: IntoAnswer, P2: IntoAnswer> Solver for Day<T, P1, P2> {
+    fn day(&self) -> u8 {
+        self.day
+    }
+
+    fn title(&self) -> &'static str {
+        self.title
+    }
+
+    fn input_type(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn parts(&self) -> Vec<PartInfo> {
+        vec![
+            PartInfo { part: 1, answer_type: type_name::<P1::Value>() },
+            PartInfo { part: 2, answer_type: type_name::<P2::Value>() },
+        ]
+    }
+
+    fn run(&self, input: &str, parts: &[u8]) -> Result<Run, AocError> {
+        let start = Instant::now();
+        let parsed = (self.generator)(input)?;
+        let generator = start.elapsed();
+
+        let answers = parts
+            .iter()
+            .filter_map(|&part| {
+                let start = Instant::now();
+                let value = match part {
+                    1 => (self.part1)(&parsed).into_answer().map(|value| value.to_string()),
+                    2 => (self.part2)(&parsed).into_answer().map(|value| value.to_string()),
+                    _ => return None,
+                };
+                Some(Answer { part, value, duration: start.elapsed() })
+            })
+            .collect();
+
+        Ok(Run { generator, answers })
+    }
+}
+
+//the closures let solvers taking slices accept the generator's Vec,
+//a generator marked with ? already returns a Result
+macro_rules! day {
+    ($module:ident, $generator:ident ?, $day:expr, $title:expr) => {
+        &Day {
+            day: $day,
+            title: $title,
+            generator: $module::$generator,
+            part1: |input| $module::solve_part1(input),
+            part2: |input| $module::solve_part2(input),
+        }
+    };
+    ($module:ident, $generator:ident, $day:expr, $title:expr) => {
+        &Day {
+            day: $day,
+            title: $title,
+            generator: |input| Ok($module::$generator(input)),
+            part1: |input| $module::solve_part1(input),
+            part2: |input| $module::solve_part2(input),
+        }
+    };
+}
+
+static SOLVERS: [&dyn Solver; 21] = [
+    day!(day01, generate_input?, 1, "Historian Hysteria"),
+    day!(day02, generate_input?, 2, "Red-Nosed Reports"),
+    day!(day03, generate_input, 3, "Mull It Over"),
+    day!(day04, generate_input?, 4, "Ceres Search"),
+    day!(day05, input_generator?, 5, "Print Queue"),
+    day!(day06, input_generator?, 6, "Guard Gallivant"),
+    day!(day07, generate_input?, 7, "Bridge Repair"),
+    day!(day08, generate_input?, 8, "Resonant Collinearity"),
+    day!(day09, generate_input?, 9, "Disk Fragmenter"),
+    day!(day10, generate_input?, 10, "Hoof It"),
+    day!(day11, generate_input?, 11, "Plutonian Pebbles"),
+    day!(day12, generate_input?, 12, "Garden Groups"),
+    day!(day13, generate_input?, 13, "Claw Contraption"),
+    day!(day14, generate_input?, 14, "Restroom Redoubt"),
+    day!(day15, generate_input?, 15, "Warehouse Woes"),
+    day!(day16, generate_input?, 16, "Reindeer Maze"),
+    day!(day17, generate_input?, 17, "Chronospatial Computer"),
+    day!(day18, generate_input?, 18, "RAM Run"),
+    day!(day19, generate_input, 19, "Linen Layout"),
+    day!(day20, generate_input?, 20, "Race Condition"),
+    day!(day21, generate_input?, 21, "Keypad Conundrum"),
+];
+
+pub fn solvers() -> &'static [&'static dyn Solver] {
+    &SOLVERS
+}
+
+pub fn solver(day: u8) -> Option<&'static dyn Solver> {
+    SOLVERS.iter().copied().find(|solver| solver.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_days() {
+        let days: Vec<u8> = solvers().iter().map(|solver| solver.day()).collect();
+        assert_eq!(days, (1..=21).collect::<Vec<u8>>());
+        assert!(solver(22).is_none());
+    }
+
+    #[test]
+    fn test_registry_metadata() {
+        let day01 = solver(1).unwrap();
+        assert_eq!(day01.title(), "Historian Hysteria");
+        assert_eq!(day01.input_type(), "(alloc::vec::Vec<i32>, alloc::vec::Vec<i32>)");
+        assert_eq!(
+            day01.parts(),
+            vec![
+                PartInfo { part: 1, answer_type: "i32" },
+                PartInfo { part: 2, answer_type: "i32" },
+            ]
+        );
+        assert_eq!(solver(17).unwrap().parts()[0].answer_type, "alloc::string::String");
+    }
+
+    #[test]
+    fn test_registry_run() {
+        let run = solver(1).unwrap().run("3   4\n4   3\n2   5\n1   3\n3   9\n3   3", &[1, 2, 3]);
+        let answers: Vec<(u8, String)> = run
+            .unwrap()
+            .answers
+            .into_iter()
+            .map(|answer| (answer.part, answer.value.unwrap()))
+            .collect();
+        assert_eq!(answers, vec![(1, "11".to_string()), (2, "31".to_string())]);
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        let error = solver(1).unwrap().run("3   4\n4   x", &[1]).unwrap_err();
+        assert_eq!(error.to_string(), "day 1, line 2, column 5: invalid number `x`");
+
+        let run = solver(17).unwrap().run("Register A: 1\n\nProgram: 5,7", &[1]).unwrap();
+        assert_eq!(
+            run.answers[0].value,
+            Err(AocError::InvalidOperand { day: 17, pointer: 0, operand: 7 })
+        );
+    }
+}
@@ -1,11 +1,330 @@
-use std::collections::HashMap;
+use crate::error::{AocError, Line};
+use std::fmt;
+use std::ops::{Index, IndexMut};
 
-pub fn create_map(input: &[String]) -> HashMap<(i16, i16), char> {
-    let mut map = HashMap::new();
-    for (row, line) in input.iter().enumerate() {
-        for (col, ch) in line.chars().enumerate() {
-            map.insert(((row) as i16, (col) as i16), ch);
+//(row, col) position inside a grid
+pub type Point = (i16, i16);
+
+//up, right, down, left
+pub const DIRS_4: [Point; 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+//clockwise starting up, diagonals included
+pub const DIRS_8: [Point; 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+//dense row-major grid, every access is bounds checked
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    //None if the rows do not all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        Some(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    //build a grid from the lines of a puzzle, convert returns None for invalid cells
+    pub fn parse_with(
+        day: u8,
+        input: &str,
+        expected: &'static str,
+        convert: impl Fn(char) -> Option<T>,
+    ) -> Result<Self, AocError> {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+
+        for (index, text) in input.lines().enumerate() {
+            let line = Line::new(day, index, text);
+            let start = cells.len();
+            for (col, ch) in text.char_indices() {
+                cells.push(convert(ch).ok_or_else(|| line.expected(&text[col..], expected))?);
+            }
+
+            let row = cells.len() - start;
+            if index == 0 {
+                width = row;
+            } else if row != width {
+                return Err(line.expected_end("a row as wide as the first one"));
+            }
+            height += 1;
+        }
+
+        Ok(Grid { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (row, col): Point) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width
+    }
+
+    fn offset(&self, pos: Point) -> Option<usize> {
+        if self.contains(pos) {
+            Some(pos.0 as usize * self.width + pos.1 as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        self.offset(pos).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        self.offset(pos).map(move |offset| &mut self.cells[offset])
+    }
+
+    //returns the previous value, None if the position is outside the grid
+    pub fn set(&mut self, pos: Point, value: T) -> Option<T> {
+        self.get_mut(pos).map(|cell| std::mem::replace(cell, value))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |offset| ((offset / width) as i16, (offset % width) as i16))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        if row < self.height {
+            Some(&self.cells[row * self.width..(row + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        let width = self.width;
+        let cells = if col < width { &self.cells[..] } else { &[] };
+        cells.iter().skip(col).step_by(width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |col| self.column(col))
+    }
+
+    //in-bounds neighbours following the given directions
+    pub fn neighbours<'a>(&'a self, pos: Point, dirs: &'a [Point]) -> impl Iterator<Item = Point> + 'a {
+        dirs.iter()
+            .map(move |&(dr, dc)| (pos.0 + dr, pos.1 + dc))
+            .filter(move |&next| self.contains(next))
+    }
+
+    pub fn neighbours4(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(pos, &DIRS_4)
+    }
+
+    pub fn neighbours8(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(pos, &DIRS_8)
+    }
+
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.iter().find(|(_, cell)| *cell == value).map(|(pos, _)| pos)
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter().filter(move |(_, cell)| *cell == value).map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, convert: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(convert).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .flat_map(|col| self.column(col).cloned())
+            .collect();
+
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+
+    //a quarter turn clockwise: the first column read upwards becomes the first row
+    pub fn rotate_cw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut rotated = self.transpose();
+        rotated.flip_horizontal();
+        rotated
+    }
+
+    pub fn rotate_ccw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut rotated = self.transpose();
+        rotated.flip_vertical();
+        rotated
+    }
+
+    //mirror every row
+    pub fn flip_horizontal(&mut self) {
+        if self.width > 0 {
+            self.cells.chunks_mut(self.width).for_each(|row| row.reverse());
+        }
+    }
+
+    //mirror every column
+    pub fn flip_vertical(&mut self) {
+        for row in 0..self.height / 2 {
+            let other = self.height - 1 - row;
+            for col in 0..self.width {
+                self.cells.swap(row * self.width + col, other * self.width + col);
+            }
         }
     }
-    map
-}
\ No newline at end of file
+}
+
+impl Grid<char> {
+    //keep every character as is, only the rows must have the same length
+    pub fn parse(day: u8, input: &str) -> Result<Self, AocError> {
+        Grid::parse_with(day, input, "a character", Some)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, pos: Point) -> &mut T {
+        self.get_mut(pos).unwrap_or_else(|| panic!("{:?} is outside the grid", pos))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse(0, "abc\ndef").unwrap()
+    }
+
+    #[test]
+    fn test_grid_access() {
+        let mut grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get((1, 2)), Some(&'f'));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.set((0, 0), 'z'), Some('a'));
+        assert_eq!(grid[(0, 0)], 'z');
+        assert_eq!(grid.find(&'e'), Some((1, 1)));
+        assert_eq!(grid.find_all(&'x').count(), 0);
+    }
+
+    #[test]
+    fn test_grid_neighbours() {
+        let grid = sample();
+        assert_eq!(grid.neighbours4((0, 0)).collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours8((0, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_grid_rows_and_columns() {
+        let grid = sample();
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.columns().map(|col| col.collect::<String>()).collect::<Vec<_>>(), vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn test_grid_transformations() {
+        let grid = sample();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw(), grid);
+    }
+
+    #[test]
+    fn test_grid_parse_errors() {
+        assert_eq!(
+            Grid::parse(4, "abc\nde"),
+            Err(AocError::UnexpectedInput { day: 4, line: 2, column: 3, expected: "a row as wide as the first one" })
+        );
+        assert_eq!(
+            Grid::parse_with(10, "01\n2x", "a digit", |ch| ch.to_digit(10)),
+            Err(AocError::UnexpectedInput { day: 10, line: 2, column: 2, expected: "a digit" })
+        );
+    }
+}